@@ -1,9 +1,10 @@
 use node_template_runtime::{
     AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-    SystemConfig, WASM_BINARY,
+    SystemConfig, SOCIAL_TOKEN_DECIMALS, SOCIAL_TOKEN_TICKER, TOKEN_DECIMALS, TOKEN_SYMBOL,
+    WASM_BINARY,
 };
 use node_template_runtime::{ContractsConfig, ContractsSchedule};
-use sc_service::ChainType;
+use sc_service::{ChainType, Properties};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -37,6 +38,18 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
     (get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
+/// Token properties shown by wallets such as Polkadot-JS, the native currency first and
+/// then the social token
+pub fn properties() -> Properties {
+    let mut properties = Properties::new();
+    properties.insert(
+        "tokenDecimals".into(),
+        vec![TOKEN_DECIMALS, SOCIAL_TOKEN_DECIMALS as u32].into(),
+    );
+    properties.insert("tokenSymbol".into(), vec![TOKEN_SYMBOL, SOCIAL_TOKEN_TICKER].into());
+    properties
+}
+
 pub fn development_config() -> ChainSpec {
     ChainSpec::from_genesis(
         "Development",
@@ -58,7 +71,7 @@ pub fn development_config() -> ChainSpec {
         vec![],
         None,
         None,
-        None,
+        Some(properties()),
         None,
     )
 }
@@ -92,7 +105,7 @@ pub fn local_testnet_config() -> ChainSpec {
         vec![],
         None,
         None,
-        None,
+        Some(properties()),
         None,
     )
 }
@@ -121,5 +134,6 @@ fn testnet_genesis(
         contracts: Some(ContractsConfig {
             current_schedule: ContractsSchedule { enable_println, ..Default::default() },
         }),
+        erc20: Some(Default::default()),
     }
 }
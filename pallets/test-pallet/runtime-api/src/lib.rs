@@ -0,0 +1,32 @@
+//! Runtime API definition for the test pallet.
+//!
+//! Lets the app query indexes kept by the pallet straight from chain state.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait SocialApi<AccountId, Report, Balance, BlockNumber> where
+        AccountId: Codec,
+        Report: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        // photo ids tagged with `tag`, skipping `start` entries and returning at most `limit`
+        fn photos_by_tag(tag: Vec<u8>, start: u32, limit: u32) -> Vec<Vec<u8>>;
+        // followers of `account`, skipping `start` entries and returning at most `limit`
+        fn followers(account: AccountId, start: u32, limit: u32) -> Vec<AccountId>;
+        // the `limit` top trending photos with their current scores, highest first
+        fn trending(limit: u32) -> Vec<(Vec<u8>, u64)>;
+        // whether `subscriber` has paid for the current period of a subscription to `creator`
+        fn is_subscribed(subscriber: AccountId, creator: AccountId) -> bool;
+        // reports waiting for a moderator with their ids, skipping `start` entries and returning at most `limit`
+        fn pending_reports(start: u32, limit: u32) -> Vec<(u64, Report)>;
+        // social token balance of `account` at the end of `block`, `None` if older than the kept checkpoints
+        fn balance_at(account: AccountId, block: BlockNumber) -> Option<Balance>;
+        // social token total supply at the end of `block`, `None` if older than the kept checkpoints
+        fn total_supply_at(block: BlockNumber) -> Option<Balance>;
+    }
+}
@@ -0,0 +1,442 @@
+// `Currency`, `ReservableCurrency` and `LockableCurrency` over the social token, so other
+// pallets can be configured with it. `BalanceOf` holds the free balance, `ReservedOf` the
+// reserved one, and imbalances square up `total_supply` when they are dropped.
+
+use crate::{BalanceOf, Locks, Module, ReservedOf, TokenInfo, Trait, SOCIAL_TOKEN};
+use frame_support::{
+    dispatch::DispatchResult,
+    ensure,
+    traits::{
+        BalanceStatus, Currency, ExistenceRequirement, Imbalance, LockIdentifier, LockableCurrency,
+        ReservableCurrency, SignedImbalance, TryDrop, WithdrawReason, WithdrawReasons,
+    },
+    StorageDoubleMap, StorageMap,
+};
+use sp_runtime::traits::{Bounded, CheckedAdd, CheckedSub, Saturating, Zero};
+use sp_runtime::DispatchError;
+use sp_std::{cmp, mem, result};
+
+/// Opaque, move-only struct with private fields that serves as a token denoting that
+/// social tokens have been created without any equal and opposite accounting.
+#[must_use]
+pub struct PositiveImbalance<T: Trait>(T::TokenBalance);
+
+impl<T: Trait> PositiveImbalance<T> {
+    /// Create a new positive imbalance from a balance.
+    pub fn new(amount: T::TokenBalance) -> Self {
+        PositiveImbalance(amount)
+    }
+}
+
+/// Opaque, move-only struct with private fields that serves as a token denoting that
+/// social tokens have been destroyed without any equal and opposite accounting.
+#[must_use]
+pub struct NegativeImbalance<T: Trait>(T::TokenBalance);
+
+impl<T: Trait> NegativeImbalance<T> {
+    /// Create a new negative imbalance from a balance.
+    pub fn new(amount: T::TokenBalance) -> Self {
+        NegativeImbalance(amount)
+    }
+}
+
+impl<T: Trait> TryDrop for PositiveImbalance<T> {
+    fn try_drop(self) -> result::Result<(), Self> {
+        self.drop_zero()
+    }
+}
+
+impl<T: Trait> Imbalance<T::TokenBalance> for PositiveImbalance<T> {
+    type Opposite = NegativeImbalance<T>;
+
+    fn zero() -> Self {
+        Self(Zero::zero())
+    }
+    fn drop_zero(self) -> result::Result<(), Self> {
+        if self.0.is_zero() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+    fn split(self, amount: T::TokenBalance) -> (Self, Self) {
+        let first = self.0.min(amount);
+        let second = self.0 - first;
+
+        mem::forget(self);
+        (Self(first), Self(second))
+    }
+    fn merge(mut self, other: Self) -> Self {
+        self.0 = self.0.saturating_add(other.0);
+        mem::forget(other);
+
+        self
+    }
+    fn subsume(&mut self, other: Self) {
+        self.0 = self.0.saturating_add(other.0);
+        mem::forget(other);
+    }
+    fn offset(self, other: Self::Opposite) -> result::Result<Self, Self::Opposite> {
+        let (a, b) = (self.0, other.0);
+        mem::forget((self, other));
+
+        if a >= b {
+            Ok(Self(a - b))
+        } else {
+            Err(NegativeImbalance::new(b - a))
+        }
+    }
+    fn peek(&self) -> T::TokenBalance {
+        self.0
+    }
+}
+
+impl<T: Trait> TryDrop for NegativeImbalance<T> {
+    fn try_drop(self) -> result::Result<(), Self> {
+        self.drop_zero()
+    }
+}
+
+impl<T: Trait> Imbalance<T::TokenBalance> for NegativeImbalance<T> {
+    type Opposite = PositiveImbalance<T>;
+
+    fn zero() -> Self {
+        Self(Zero::zero())
+    }
+    fn drop_zero(self) -> result::Result<(), Self> {
+        if self.0.is_zero() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+    fn split(self, amount: T::TokenBalance) -> (Self, Self) {
+        let first = self.0.min(amount);
+        let second = self.0 - first;
+
+        mem::forget(self);
+        (Self(first), Self(second))
+    }
+    fn merge(mut self, other: Self) -> Self {
+        self.0 = self.0.saturating_add(other.0);
+        mem::forget(other);
+
+        self
+    }
+    fn subsume(&mut self, other: Self) {
+        self.0 = self.0.saturating_add(other.0);
+        mem::forget(other);
+    }
+    fn offset(self, other: Self::Opposite) -> result::Result<Self, Self::Opposite> {
+        let (a, b) = (self.0, other.0);
+        mem::forget((self, other));
+
+        if a >= b {
+            Ok(Self(a - b))
+        } else {
+            Err(PositiveImbalance::new(b - a))
+        }
+    }
+    fn peek(&self) -> T::TokenBalance {
+        self.0
+    }
+}
+
+impl<T: Trait> Drop for PositiveImbalance<T> {
+    // tokens were credited to an account, so they join the total supply
+    fn drop(&mut self) {
+        let previous = <TokenInfo<T>>::mutate(SOCIAL_TOKEN, |token| {
+            let previous = token.total_supply;
+            token.total_supply = token.total_supply.saturating_add(self.0);
+            previous
+        });
+        <Module<T>>::_checkpoint_supply(SOCIAL_TOKEN, previous);
+    }
+}
+
+impl<T: Trait> Drop for NegativeImbalance<T> {
+    // tokens were taken from an account, so they leave the total supply
+    fn drop(&mut self) {
+        let previous = <TokenInfo<T>>::mutate(SOCIAL_TOKEN, |token| {
+            let previous = token.total_supply;
+            token.total_supply = token.total_supply.saturating_sub(self.0);
+            previous
+        });
+        <Module<T>>::_checkpoint_supply(SOCIAL_TOKEN, previous);
+    }
+}
+
+impl<T: Trait> Module<T> {
+    fn _set_free(who: &T::AccountId, value: T::TokenBalance) {
+        Self::_set_balance(SOCIAL_TOKEN, who, value);
+    }
+}
+
+impl<T: Trait> Currency<T::AccountId> for Module<T> {
+    type Balance = T::TokenBalance;
+    type PositiveImbalance = PositiveImbalance<T>;
+    type NegativeImbalance = NegativeImbalance<T>;
+
+    fn total_balance(who: &T::AccountId) -> Self::Balance {
+        Self::free_balance(who).saturating_add(Self::reserved_of(who))
+    }
+
+    fn can_slash(who: &T::AccountId, value: Self::Balance) -> bool {
+        value.is_zero() || (!Self::frozen(who.clone()) && Self::free_balance(who) >= value)
+    }
+
+    fn total_issuance() -> Self::Balance {
+        Self::token_info(SOCIAL_TOKEN).total_supply
+    }
+
+    // there are no existential deposits for the social token
+    fn minimum_balance() -> Self::Balance {
+        Zero::zero()
+    }
+
+    fn burn(mut amount: Self::Balance) -> Self::PositiveImbalance {
+        if amount.is_zero() {
+            return PositiveImbalance::zero();
+        }
+        let previous = <TokenInfo<T>>::mutate(SOCIAL_TOKEN, |token| {
+            let previous = token.total_supply;
+            token.total_supply = token.total_supply.checked_sub(&amount).unwrap_or_else(|| {
+                amount = token.total_supply;
+                Zero::zero()
+            });
+            previous
+        });
+        Self::_checkpoint_supply(SOCIAL_TOKEN, previous);
+        PositiveImbalance::new(amount)
+    }
+
+    fn issue(mut amount: Self::Balance) -> Self::NegativeImbalance {
+        if amount.is_zero() {
+            return NegativeImbalance::zero();
+        }
+        let previous = <TokenInfo<T>>::mutate(SOCIAL_TOKEN, |token| {
+            let previous = token.total_supply;
+            token.total_supply = token.total_supply.checked_add(&amount).unwrap_or_else(|| {
+                amount = Self::Balance::max_value() - token.total_supply;
+                Self::Balance::max_value()
+            });
+            previous
+        });
+        Self::_checkpoint_supply(SOCIAL_TOKEN, previous);
+        NegativeImbalance::new(amount)
+    }
+
+    fn free_balance(who: &T::AccountId) -> Self::Balance {
+        Self::balance_of(SOCIAL_TOKEN, who)
+    }
+
+    // locks apply to every withdraw reason
+    fn ensure_can_withdraw(
+        who: &T::AccountId,
+        amount: Self::Balance,
+        _reasons: WithdrawReasons,
+        new_balance: Self::Balance,
+    ) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        ensure!(!Self::frozen(who.clone()), "Account is frozen");
+        ensure!(new_balance >= Self::_locked(who), "Balance is locked");
+        Ok(())
+    }
+
+    fn transfer(
+        source: &T::AccountId,
+        dest: &T::AccountId,
+        value: Self::Balance,
+        _existence_requirement: ExistenceRequirement,
+    ) -> DispatchResult {
+        if value.is_zero() || source == dest {
+            return Ok(());
+        }
+        Self::_transfer(SOCIAL_TOKEN, source.clone(), dest.clone(), value)
+    }
+
+    // slashes the free balance first, then the reserved one, nothing of a frozen account
+    fn slash(who: &T::AccountId, value: Self::Balance) -> (Self::NegativeImbalance, Self::Balance) {
+        if value.is_zero() {
+            return (NegativeImbalance::zero(), Zero::zero());
+        }
+        if Self::frozen(who.clone()) {
+            return (NegativeImbalance::zero(), value);
+        }
+        let free = Self::free_balance(who);
+        let from_free = cmp::min(free, value);
+        Self::_set_free(who, free - from_free);
+
+        let reserved = Self::reserved_of(who);
+        let from_reserved = cmp::min(reserved, value - from_free);
+        <ReservedOf<T>>::insert(who, reserved - from_reserved);
+
+        let slashed = from_free + from_reserved;
+        (NegativeImbalance::new(slashed), value - slashed)
+    }
+
+    fn deposit_into_existing(
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> result::Result<Self::PositiveImbalance, DispatchError> {
+        if value.is_zero() {
+            return Ok(PositiveImbalance::zero());
+        }
+        ensure!(<BalanceOf<T>>::contains_key(SOCIAL_TOKEN, who), "Account does not own this token");
+        Self::_credit(who.clone(), value)?;
+        Ok(PositiveImbalance::new(value))
+    }
+
+    fn deposit_creating(who: &T::AccountId, value: Self::Balance) -> Self::PositiveImbalance {
+        if value.is_zero() || Self::_credit(who.clone(), value).is_err() {
+            return PositiveImbalance::zero();
+        }
+        PositiveImbalance::new(value)
+    }
+
+    fn withdraw(
+        who: &T::AccountId,
+        value: Self::Balance,
+        reasons: WithdrawReasons,
+        _liveness: ExistenceRequirement,
+    ) -> result::Result<Self::NegativeImbalance, DispatchError> {
+        if value.is_zero() {
+            return Ok(NegativeImbalance::zero());
+        }
+        let new_balance = Self::free_balance(who).checked_sub(&value).ok_or("Not enough balance.")?;
+        Self::ensure_can_withdraw(who, value, reasons, new_balance)?;
+        Self::_set_free(who, new_balance);
+        Ok(NegativeImbalance::new(value))
+    }
+
+    fn make_free_balance_be(
+        who: &T::AccountId,
+        balance: Self::Balance,
+    ) -> SignedImbalance<Self::Balance, Self::PositiveImbalance> {
+        let original = Self::free_balance(who);
+        Self::_set_free(who, balance);
+        if balance > original {
+            SignedImbalance::Positive(PositiveImbalance::new(balance - original))
+        } else {
+            SignedImbalance::Negative(NegativeImbalance::new(original - balance))
+        }
+    }
+}
+
+impl<T: Trait> ReservableCurrency<T::AccountId> for Module<T> {
+    fn can_reserve(who: &T::AccountId, value: Self::Balance) -> bool {
+        if value.is_zero() {
+            return true;
+        }
+        Self::free_balance(who).checked_sub(&value).map_or(false, |new_balance| {
+            Self::ensure_can_withdraw(who, value, WithdrawReason::Reserve.into(), new_balance).is_ok()
+        })
+    }
+
+    fn slash_reserved(who: &T::AccountId, value: Self::Balance) -> (Self::NegativeImbalance, Self::Balance) {
+        if value.is_zero() {
+            return (NegativeImbalance::zero(), Zero::zero());
+        }
+        if Self::frozen(who.clone()) {
+            return (NegativeImbalance::zero(), value);
+        }
+        let reserved = Self::reserved_of(who);
+        let actual = cmp::min(reserved, value);
+        <ReservedOf<T>>::insert(who, reserved - actual);
+        (NegativeImbalance::new(actual), value - actual)
+    }
+
+    fn reserved_balance(who: &T::AccountId) -> Self::Balance {
+        Self::reserved_of(who)
+    }
+
+    fn reserve(who: &T::AccountId, value: Self::Balance) -> DispatchResult {
+        if value.is_zero() {
+            return Ok(());
+        }
+        let new_balance = Self::free_balance(who).checked_sub(&value).ok_or("Not enough balance.")?;
+        Self::ensure_can_withdraw(who, value, WithdrawReason::Reserve.into(), new_balance)?;
+        let reserved = Self::reserved_of(who).checked_add(&value).ok_or("overflow in calculating balance")?;
+        Self::_set_free(who, new_balance);
+        <ReservedOf<T>>::insert(who, reserved);
+        Ok(())
+    }
+
+    fn unreserve(who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+        if value.is_zero() {
+            return Zero::zero();
+        }
+        let reserved = Self::reserved_of(who);
+        let actual = cmp::min(reserved, value);
+        <ReservedOf<T>>::insert(who, reserved - actual);
+        Self::_set_free(who, Self::free_balance(who).saturating_add(actual));
+        value - actual
+    }
+
+    fn repatriate_reserved(
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> result::Result<Self::Balance, DispatchError> {
+        if value.is_zero() {
+            return Ok(Zero::zero());
+        }
+        ensure!(!Self::frozen(slashed.clone()) && !Self::frozen(beneficiary.clone()), "Account is frozen");
+        if slashed == beneficiary {
+            return match status {
+                BalanceStatus::Free => Ok(Self::unreserve(slashed, value)),
+                BalanceStatus::Reserved => Ok(value.saturating_sub(Self::reserved_of(slashed))),
+            };
+        }
+        let reserved = Self::reserved_of(slashed);
+        let actual = cmp::min(reserved, value);
+        match status {
+            BalanceStatus::Free => Self::_credit(beneficiary.clone(), actual)?,
+            BalanceStatus::Reserved => {
+                let updated = Self::reserved_of(beneficiary)
+                    .checked_add(&actual)
+                    .ok_or("overflow in calculating balance")?;
+                <ReservedOf<T>>::insert(beneficiary, updated);
+            }
+        }
+        <ReservedOf<T>>::insert(slashed, reserved - actual);
+        Ok(value - actual)
+    }
+}
+
+impl<T: Trait> LockableCurrency<T::AccountId> for Module<T> {
+    type Moment = T::BlockNumber;
+
+    fn set_lock(id: LockIdentifier, who: &T::AccountId, amount: Self::Balance, reasons: WithdrawReasons) {
+        if amount.is_zero() || reasons.is_none() {
+            Self::remove_lock(id, who);
+            return;
+        }
+        <Locks<T>>::mutate(who, |locks| {
+            locks.retain(|(lock_id, _)| *lock_id != id);
+            locks.push((id, amount));
+        });
+    }
+
+    fn extend_lock(id: LockIdentifier, who: &T::AccountId, amount: Self::Balance, reasons: WithdrawReasons) {
+        if amount.is_zero() || reasons.is_none() {
+            return;
+        }
+        <Locks<T>>::mutate(who, |locks| {
+            match locks.iter_mut().find(|(lock_id, _)| *lock_id == id) {
+                Some((_, locked)) => *locked = cmp::max(*locked, amount),
+                None => locks.push((id, amount)),
+            }
+        });
+    }
+
+    fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
+        <Locks<T>>::mutate(who, |locks| locks.retain(|(lock_id, _)| *lock_id != id));
+        if Self::locks(who).is_empty() {
+            <Locks<T>>::remove(who);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// Lets a signer pay transaction fees in the social token instead of the native currency.
+// Replaces `ChargeTransactionPayment` in the runtime's `SignedExtra`: when `in_token` is
+// false the native payment is charged as before, otherwise the fee is converted at the
+// root-set `FeeRate` and the tokens go to the `Treasury`.
+// `LimitFreeActions` caps how many feeless social actions an account makes per era.
+
+use crate::{Call, Module, Trait};
+use codec::{Decode, Encode};
+use frame_support::{
+    dispatch::DispatchResult,
+    traits::{Currency, IsSubType},
+    weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_std::marker::PhantomData;
+use sp_runtime::{
+    traits::{
+        CheckedSub, DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, SignedExtension,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError,
+        ValidTransaction,
+    },
+    FixedPointNumber, FixedPointOperand,
+};
+
+type FeeBalanceOf<T> = <<T as pallet_transaction_payment::Trait>::Currency as Currency<
+    <T as frame_system::Trait>::AccountId,
+>>::Balance;
+
+/// Pays the transaction fee and tip either in the native currency or in the social token.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeFeeInToken<T: Trait + pallet_transaction_payment::Trait + Send + Sync> {
+    #[codec(compact)]
+    tip: FeeBalanceOf<T>,
+    in_token: bool,
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> ChargeFeeInToken<T> {
+    /// Pay the fee and `tip` in the native currency.
+    pub fn native(tip: FeeBalanceOf<T>) -> Self {
+        Self { tip, in_token: false }
+    }
+
+    /// Pay the fee and `tip` in the social token.
+    pub fn token(tip: FeeBalanceOf<T>) -> Self {
+        Self { tip, in_token: true }
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> From<FeeBalanceOf<T>> for ChargeFeeInToken<T> {
+    fn from(tip: FeeBalanceOf<T>) -> Self {
+        Self::native(tip)
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> sp_std::fmt::Debug for ChargeFeeInToken<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "ChargeFeeInToken<{:?}, {}>", self.tip, self.in_token)
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> ChargeFeeInToken<T>
+where
+    FeeBalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+    T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+    // native fee and its price in social tokens, fails if the account can't cover the latter
+    fn token_fee(
+        &self,
+        who: &T::AccountId,
+        info: &DispatchInfoOf<T::Call>,
+        len: usize,
+    ) -> Result<(FeeBalanceOf<T>, T::TokenBalance), TransactionValidityError> {
+        let rate = <Module<T>>::fee_rate().ok_or(InvalidTransaction::Payment)?;
+        if !<Module<T>>::initialized() {
+            return Err(InvalidTransaction::Payment.into());
+        }
+        // `pre_dispatch` moves the tokens from the signer to the treasury, neither may be frozen
+        if <Module<T>>::frozen(who) || <Module<T>>::frozen(<Module<T>>::treasury()) {
+            return Err(InvalidTransaction::Payment.into());
+        }
+        let fee = <pallet_transaction_payment::Module<T>>::compute_fee(len as u32, info, self.tip);
+        let token_fee = T::TokenBalance::from(rate.saturating_mul_int(fee.saturated_into::<u128>()));
+
+        let balance = <Module<T>>::balance_of(crate::SOCIAL_TOKEN, who);
+        let remaining = balance.checked_sub(&token_fee).ok_or(InvalidTransaction::Payment)?;
+        <Module<T>>::_ensure_unlocked(crate::SOCIAL_TOKEN, who, remaining)
+            .map_err(|_| InvalidTransaction::Payment)?;
+        Ok((fee, token_fee))
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> SignedExtension for ChargeFeeInToken<T>
+where
+    FeeBalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+    T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+    const IDENTIFIER: &'static str = "ChargeFeeInToken";
+    type AccountId = T::AccountId;
+    type Call = T::Call;
+    type AdditionalSigned = ();
+    // `None` when the fee was paid in tokens, those are not refunded
+    type Pre = Option<<ChargeTransactionPayment<T> as SignedExtension>::Pre>;
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        if !self.in_token {
+            return ChargeTransactionPayment::<T>::from(self.tip).validate(who, call, info, len);
+        }
+        let (fee, _) = self.token_fee(who, info, len)?;
+        Ok(ValidTransaction {
+            priority: fee.saturated_into::<TransactionPriority>(),
+            ..Default::default()
+        })
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        if !self.in_token {
+            return ChargeTransactionPayment::<T>::from(self.tip)
+                .pre_dispatch(who, call, info, len)
+                .map(Some);
+        }
+        let (_, token_fee) = self.token_fee(who, info, len)?;
+        <Module<T>>::_debit(who.clone(), token_fee).map_err(|_| InvalidTransaction::Payment)?;
+        <Module<T>>::_credit(<Module<T>>::treasury(), token_fee).map_err(|_| InvalidTransaction::Payment)?;
+        Ok(None)
+    }
+
+    fn post_dispatch(
+        pre: Self::Pre,
+        info: &DispatchInfoOf<Self::Call>,
+        post_info: &PostDispatchInfoOf<Self::Call>,
+        len: usize,
+        result: &DispatchResult,
+    ) -> Result<(), TransactionValidityError> {
+        match pre {
+            Some(pre) => ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rejects `like_photo`, `comment_photo` and `update_user` once the signer has used up
+/// `FreeActionsPerEra`, since those calls don't pay fees.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct LimitFreeActions<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> LimitFreeActions<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Trait + Send + Sync> Default for LimitFreeActions<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for LimitFreeActions<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "LimitFreeActions")
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> LimitFreeActions<T>
+where
+    T::Call: IsSubType<Module<T>, T>,
+{
+    fn is_free(call: &T::Call) -> bool {
+        match call.is_sub_type() {
+            Some(Call::like_photo(..)) | Some(Call::comment_photo(..)) | Some(Call::update_user(..)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for LimitFreeActions<T>
+where
+    T::Call: IsSubType<Module<T>, T>,
+{
+    const IDENTIFIER: &'static str = "LimitFreeActions";
+    type AccountId = T::AccountId;
+    type Call = T::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        if Self::is_free(call) && <Module<T>>::_free_actions_left(who) == 0 {
+            return Err(InvalidTransaction::ExhaustsResources.into());
+        }
+        Ok(ValidTransaction::default())
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        self.validate(who, call, info, len)?;
+        if Self::is_free(call) {
+            <Module<T>>::_use_free_action(who);
+        }
+        Ok(())
+    }
+}
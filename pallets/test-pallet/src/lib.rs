@@ -11,19 +11,123 @@ use dispatch::{DispatchResult, Parameter};
 /// For more guidance on Substrate FRAME, see the example pallet
 /// https://github.com/paritytech/substrate/blob/master/frame/example/src/lib.rs
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, StorageMap,
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    traits::{
+        Currency, Get, LockIdentifier, LockableCurrency, Randomness, ReservableCurrency, WithdrawReasons,
+    },
+    weights::{Pays, Weight},
+    IterableStorageDoubleMap, IterableStorageMap, StorageDoubleMap, StorageMap, StorageValue,
 };
-use frame_system::{self as system, ensure_signed};
-use sp_runtime::traits::{CheckedAdd, CheckedSub, Member};
+use frame_system::{self as system, ensure_root, ensure_signed};
+use sp_runtime::traits::{
+    AccountIdConversion, AtLeast32BitUnsigned, CheckedAdd, CheckedSub, Hash, MaybeSerializeDeserialize,
+    Member, One, Saturating, Zero,
+};
+use sp_runtime::{DispatchError, FixedU128, ModuleId, Perbill, SaturatedConversion};
+use sp_std::collections::btree_map::BTreeMap;
 use sp_std::if_std;
 use sp_std::vec::Vec;
 
+mod currency;
+pub use currency::{NegativeImbalance, PositiveImbalance};
+
+mod fees;
+pub use fees::{ChargeFeeInToken, LimitFreeActions};
+
+mod migration;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+// bounds on the profile fields
+pub const MAX_NAME_LEN: usize = 64;
+pub const MAX_AVATAR_LEN: usize = 128;
+pub const MAX_BIO_LEN: usize = 512;
+pub const MAX_LINKS: usize = 5;
+pub const MAX_LINK_LEN: usize = 256;
+
+// social tokens rewarded once to every account, see `update_user`
+pub const SIGNUP_BONUS: u128 = 100;
+
+// bounds on the descriptive metadata attached to a photo
+pub const MAX_CAPTION_LEN: usize = 512;
+pub const MAX_TAGS: usize = 10;
+pub const MAX_TAG_LEN: usize = 32;
+
+pub const MAX_COMMENT_LEN: usize = 512;
+// comments a photo holds at once, also bounds the comments `delete_photo` goes through
+pub const MAX_COMMENTS_PER_PHOTO: u32 = 100;
+pub const MAX_MENTIONS: usize = 10;
+pub const MAX_TIP_MESSAGE_LEN: usize = 256;
+pub const MAX_APPEAL_LEN: usize = 512;
+pub const MAX_WRAPPED_KEY_LEN: usize = 256;
+
+// trending points added per interaction with a photo
+pub const TRENDING_LIKE_POINTS: u64 = 1_000;
+pub const TRENDING_COMMENT_POINTS: u64 = 2_000;
+pub const TRENDING_VARIANT_POINTS: u64 = 3_000;
+
+// bounds on user albums
+pub const MAX_ALBUM_NAME_LEN: usize = 64;
+pub const MAX_ALBUM_PHOTOS: usize = 500;
+// also bounds the albums `delete_photo` goes through
+pub const MAX_ALBUMS: u32 = 100;
+
+// bounds on handles, see `_ensure_valid_handle` for the allowed characters
+pub const MIN_HANDLE_LEN: usize = 3;
+pub const MAX_HANDLE_LEN: usize = 32;
+
+pub type AlbumId = u32;
+pub type ReportId = u64;
+pub type DisputeId = u64;
+pub type TierId = u32;
+pub type TokenId = u32;
+pub type AirdropId = u32;
+
+// the token created by `init`, used for tips, subscriptions, juries and rewards
+pub const SOCIAL_TOKEN: TokenId = 0;
+
+// bounds on the token metadata
+pub const MAX_TOKEN_ICON_LEN: usize = 128;
+pub const MAX_TOKEN_DESCRIPTION_LEN: usize = 512;
+// 10^38 is the largest power of ten that fits a u128 balance
+pub const MAX_TOKEN_DECIMALS: u8 = 38;
+
+pub const MAX_TIER_NAME_LEN: usize = 64;
+
+// bounds on batch transfers and airdrop claims, a proof of 32 hashes covers 2^32 recipients
+pub const MAX_BATCH_TRANSFERS: usize = 1000;
+pub const MAX_PROOF_LEN: usize = 32;
+
+// every airdrop escrows its tokens in a sub-account of this id, see `_airdrop_account`
+pub const AIRDROP_MODULE_ID: ModuleId = ModuleId(*b"shn/drop");
+
+// lock holding the unvested part of rewards
+pub const VESTING_ID: LockIdentifier = *b"vesting ";
+// most vesting schedules an account has running at once
+pub const MAX_VESTING_SCHEDULES: usize = 10;
+
+// checkpoints kept per balance and per total supply, older ones are dropped
+pub const MAX_CHECKPOINTS: usize = 100;
+
+// most accounts kept in the juror pool
+pub const MAX_JUROR_POOL: usize = 1_000;
+
+// most photos paid out of a finished reward epoch per block
+pub const MAX_EPOCH_PAYOUTS: usize = 100;
+
+// most subscriptions renewed per block, the rest wait for the next block
+pub const MAX_RENEWALS_PER_BLOCK: usize = 100;
+// most blocks of `RenewalsAt` gone through per block when catching up after a pause
+pub const MAX_RENEWAL_BLOCKS_PER_BLOCK: u32 = 100;
+
+// balance of the currency used for deposits, not to be confused with the `BalanceOf` storage
+pub type DepositBalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
 /// The pallet's configuration trait.
 pub trait Trait: system::Trait {
     // Add other types and constants required to configure this pallet.
@@ -38,7 +142,64 @@ pub trait Trait: system::Trait {
         + CheckedAdd
         + CheckedSub
         + PartialEq
-        + PartialOrd;
+        + PartialOrd
+        + AtLeast32BitUnsigned
+        + MaybeSerializeDeserialize;
+    // currency used to reserve deposits
+    type Currency: ReservableCurrency<Self::AccountId>;
+    // deposit reserved while an account holds a handle
+    type HandleDeposit: Get<DepositBalanceOf<Self>>;
+    // deposit reserved for every byte of photo data and of profile bios and links kept in storage
+    // profile names and avatars are free so accounts without a balance can still onboard
+    type DepositPerByte: Get<DepositBalanceOf<Self>>;
+    // social tokens reserved from the author of every comment, which keeps feeless comments free
+    // of native funds while still paying for their storage
+    type CommentDeposit: Get<Self::TokenBalance>;
+    // number of blocks in a reward era
+    type RewardEraLength: Get<Self::BlockNumber>;
+    // most tokens a single account can earn from actions within one era
+    type MaxRewardPerEra: Get<Self::TokenBalance>;
+    // blocks an account needs to exist for before its likes count and it earns rewards
+    type MinAccountAge: Get<Self::BlockNumber>;
+    // number of blocks between two reward pool distributions
+    type RewardEpochLength: Get<Self::BlockNumber>;
+    // tokens shared between creators at the end of every epoch
+    type RewardPool: Get<Self::TokenBalance>;
+    // share of a trending score kept from one block to the next
+    type TrendingDecay: Get<Perbill>;
+    // number of photos kept in the trending list
+    type TrendingSize: Get<u32>;
+    // source of randomness for drawing juries
+    type Randomness: Randomness<Self::Hash>;
+    // number of jurors drawn for a dispute
+    type JurySize: Get<u32>;
+    // tokens each juror and the dispute opener put at stake
+    type JuryStake: Get<Self::TokenBalance>;
+    // blocks jurors have to commit their vote, and then to reveal it
+    type CommitPeriod: Get<Self::BlockNumber>;
+    type RevealPeriod: Get<Self::BlockNumber>;
+    // blocks covered by one subscription payment
+    type SubscriptionPeriod: Get<Self::BlockNumber>;
+    // feeless likes, comments and profile updates an account can make within one era
+    type FreeActionsPerEra: Get<u32>;
+    // part of every reward that vests instead of being liquid right away
+    type VestedRewardShare: Get<Perbill>;
+    // blocks over which the vested part of a reward unlocks
+    type VestingPeriod: Get<Self::BlockNumber>;
+    // decimals and ticker of the social token, the runtime exposes the same values in the chain properties
+    type SocialTokenDecimals: Get<u8>;
+    type SocialTokenTicker: Get<Vec<u8>>;
+}
+
+// why a reward was not minted
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithheldReason {
+    // no profile yet, or created less than `MinAccountAge` blocks ago
+    AccountTooNew,
+    // `MaxRewardPerEra` already reached in the current era
+    EraCapReached,
+    // frozen accounts can't receive tokens
+    AccountFrozen,
 }
 
 // struct to store the token details
@@ -47,46 +208,302 @@ pub struct Erc20Token<U> {
     name: Vec<u8>,
     ticker: Vec<u8>,
     total_supply: U,
+    // balances are in units of 10^-decimals tokens
+    decimals: u8,
+    // content id of the token icon
+    icon: Vec<u8>,
+    description: Vec<u8>,
+}
+
+// layout of the pallet storage, checked by `on_runtime_upgrade`
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Debug)]
+enum Releases {
+    // the first release, a single token and no social features beyond profiles and photos
+    V1,
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
-pub struct AccountProfile {
+pub struct AccountProfile<Balance> {
     name: Vec<u8>,
     avatar: Vec<u8>,
+    bio: Vec<u8>,
+    links: Vec<Vec<u8>>,
     photos: Vec<Vec<u8>>,
+    followers: u32,
+    following: u32,
+    // reserved for the bio and links, follows their current size
+    deposit: Balance,
+}
+
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct Comment<AccountId, Balance> {
+    author: AccountId,
+    text: Vec<u8>,
+    // social tokens reserved from the author until the comment or its photo is deleted
+    deposit: Balance,
+    // set by a moderator
+    hidden: bool,
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
-pub struct PhotoInfo<AccountId> {
+pub struct PhotoInfo<AccountId, Balance> {
     owner: AccountId,
     affiliate_url: Option<Vec<u8>>,
     likes: Vec<AccountId>,
     variants: Vec<Vec<u8>>,
-    comments: Vec<Vec<u8>>,
+    caption: Option<Vec<u8>>,
+    // normalized (lowercase) tags, each one indexed in `PhotosByTag`
+    tags: Vec<Vec<u8>>,
+    // reserved from the owner until the photo is deleted
+    deposit: Balance,
+    // set by a moderator, hidden photos can't be interacted with
+    hidden: bool,
+    // the id of a private photo is its encrypted CID, only the owner and accounts
+    // holding a wrapped key in `AccessKeys` can see and interact with it
+    private: bool,
+}
+
+// content a report is about
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum ContentRef {
+    Photo(Vec<u8>),
+    // photo, index of the comment
+    Comment(Vec<u8>, u32),
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReportReason {
+    Spam,
+    Nudity,
+    Violence,
+    Harassment,
+    HateSpeech,
+    Copyright,
+    Other,
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReportStatus {
+    // waiting for a moderator
+    Open,
+    // content hidden by a moderator
+    Upheld,
+    // content left or restored by a moderator
+    Dismissed,
+    // the content owner disputes an upheld report, waiting for a moderator
+    Appealed,
+    // the content was deleted before a moderator got to the report
+    Closed,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct Report<AccountId, BlockNumber> {
+    reporter: AccountId,
+    content: ContentRef,
+    reason: ReportReason,
+    created: BlockNumber,
+    status: ReportStatus,
+    // statement of the content owner, an upheld report can only be appealed once
+    appeal: Option<Vec<u8>>,
+}
+
+// a named, ordered collection of the owner's photos
+// visibility is only a hint for the app, the data itself is public chain state
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct Album {
+    name: Vec<u8>,
+    photos: Vec<Vec<u8>>,
+    public: bool,
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
 pub struct AffiliateProvider<TokenBalance, AccountId> {
+    // token the campaign pays out in
+    token_id: TokenId,
     single_click_credit: TokenBalance,
     total_credit: TokenBalance,
     payer: AccountId,
     url_append: Vec<u8>
 }
 
+// a request to flip the visibility of a photo, decided by a randomly drawn jury
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct Dispute<AccountId, Hash, BlockNumber, Balance> {
+    photo: Vec<u8>,
+    opener: AccountId,
+    // visibility the opener asks for
+    hide: bool,
+    jurors: Vec<AccountId>,
+    // juror => hash of the encoded `(juror, dispute_id, hide, salt)` vote
+    commits: Vec<(AccountId, Hash)>,
+    votes: Vec<(AccountId, bool)>,
+    commit_end: BlockNumber,
+    reveal_end: BlockNumber,
+    // escrowed from the opener and from every juror
+    stake: Balance,
+}
+
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct SubscriptionTier<Balance> {
+    name: Vec<u8>,
+    // charged every `SubscriptionPeriod`
+    price: Balance,
+}
+
+// rewards that unlock linearly over `period` blocks from `start`
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct VestingSchedule<Balance, BlockNumber> {
+    // locked at `start`
+    locked: Balance,
+    start: BlockNumber,
+    period: BlockNumber,
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy, BlockNumber: AtLeast32BitUnsigned + Copy> VestingSchedule<Balance, BlockNumber> {
+    // tokens still locked at block `n`, `locked * (period - elapsed) / period`
+    // kept as a fraction so rewards smaller than the period don't unlock a token per block
+    fn locked_at(&self, n: BlockNumber) -> Balance {
+        let elapsed = n.saturating_sub(self.start);
+        if elapsed >= self.period {
+            return Zero::zero();
+        }
+        let remaining = (self.period - elapsed).saturated_into::<u32>();
+        Perbill::from_rational_approximation(remaining, self.period.saturated_into::<u32>()) * self.locked
+    }
+}
+
+// tokens escrowed in the airdrop's own account, claimable by the leaves `hash_of(&(account, amount))`
+// of a Merkle tree with sorted pairs
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct Airdrop<AccountId, Hash, Balance> {
+    token_id: TokenId,
+    funder: AccountId,
+    merkle_root: Hash,
+    // escrowed tokens not claimed yet
+    remaining: Balance,
+}
+
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct Subscription<Balance, BlockNumber> {
+    tier: TierId,
+    // price of the tier when subscribing, later tier price changes don't apply
+    price: Balance,
+    paid_until: BlockNumber,
+    // a cancelled subscription stays active until `paid_until` and is not renewed
+    cancelled: bool,
+}
+
 // storage for this module
 decl_storage! {
   trait Store for Module<T: Trait> as Erc20 {
       Initialized get(fn initialized): bool;
+      // new chains start on the current layout, only chains from the first release are migrated
+      StorageVersion build(|_| Releases::V2): Releases;
+      // frozen accounts can't send or receive tokens, only `make_free_balance_be` still sets their balance
+      Frozen get(fn frozen): map hasher(blake2_128_concat) T::AccountId => bool;
+      // while set no tokens can be transferred
+      Paused get(fn paused): bool;
       Treasury get(fn treasury): T::AccountId;
-      TokenInfo get(fn token_info): Erc20Token<T::TokenBalance>;
+      TokenInfo get(fn token_info): map hasher(twox_64_concat) TokenId => Erc20Token<T::TokenBalance>;
+      // account that created the token
+      TokenIssuer get(fn token_issuer): map hasher(twox_64_concat) TokenId => T::AccountId;
+      // ids below this one are taken, `SOCIAL_TOKEN` is reserved for `init`
+      NextTokenId get(fn next_token_id): TokenId = 1;
       // balances mapping for an account and token
-      BalanceOf get(fn balance_of): map hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+      BalanceOf get(fn balance_of): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
       // allowance for an account and token
-      Allowance get(fn allowance): map hasher(blake2_128_concat) (T::AccountId, T::AccountId) => T::TokenBalance;
-      Accounts get(fn accounts): map hasher(blake2_128_concat) T::AccountId => AccountProfile;
-      Photos get(fn photos): map hasher(blake2_128_concat) Vec<u8> => PhotoInfo<T::AccountId>;
+      Allowance get(fn allowance): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) (T::AccountId, T::AccountId) => T::TokenBalance;
+      // (block, balance) after every block that changed `BalanceOf`, oldest first, the first entry
+      // is the balance from before the first checkpointed change at block zero
+      BalanceCheckpoints get(fn balance_checkpoints): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => Vec<(T::BlockNumber, T::TokenBalance)>;
+      // (block, total supply) after every block that changed `TokenInfo.total_supply`, oldest first
+      SupplyCheckpoints get(fn supply_checkpoints): map hasher(twox_64_concat) TokenId => Vec<(T::BlockNumber, T::TokenBalance)>;
+      // social tokens held back from `BalanceOf` through `ReservableCurrency`
+      ReservedOf get(fn reserved_of): map hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+      // social token locks placed through `LockableCurrency`, the largest one applies
+      Locks get(fn locks): map hasher(blake2_128_concat) T::AccountId => Vec<(LockIdentifier, T::TokenBalance)>;
+      // social tokens charged per unit of native fee, paying fees in tokens is off while unset
+      FeeRate get(fn fee_rate): Option<FixedU128>;
+      // era and number of feeless actions an account made in it
+      FreeActionsUsed get(fn free_actions_used): map hasher(blake2_128_concat) T::AccountId => (T::BlockNumber, u32);
+      // unvested rewards, one schedule per reward
+      Vesting get(fn vesting): map hasher(blake2_128_concat) T::AccountId => Vec<VestingSchedule<T::TokenBalance, T::BlockNumber>>;
+      Accounts get(fn accounts): map hasher(blake2_128_concat) T::AccountId => AccountProfile<DepositBalanceOf<T>>;
+      Photos get(fn photos): map hasher(blake2_128_concat) Vec<u8> => PhotoInfo<T::AccountId, DepositBalanceOf<T>>;
+      // photo, index => comment, indexes aren't reused so reports and mentions keep pointing at the same comment
+      Comments get(fn comments): double_map hasher(blake2_128_concat) Vec<u8>, hasher(twox_64_concat) u32 => Option<Comment<T::AccountId, T::TokenBalance>>;
+      // photo => index of its next comment
+      NextCommentIndex get(fn next_comment_index): map hasher(blake2_128_concat) Vec<u8> => u32;
+      // photo => comments it holds, at most `MAX_COMMENTS_PER_PHOTO`
+      CommentCount get(fn comment_count): map hasher(blake2_128_concat) Vec<u8> => u32;
       // Website url => affiliation providers
       Affiliations get(fn affiliations): map hasher(blake2_128_concat) Vec<u8> => Vec<AffiliateProvider<T::TokenBalance, T::AccountId>>;
+      // hash of a normalized tag, photo carrying that tag => ()
+      PhotosByTag: double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) Vec<u8> => ();
+      // owner, album id => album
+      Albums get(fn albums): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) AlbumId => Option<Album>;
+      NextAlbumId get(fn next_album_id): map hasher(blake2_128_concat) T::AccountId => AlbumId;
+      AlbumCount get(fn album_count): map hasher(blake2_128_concat) T::AccountId => u32;
+      // followee, follower => block the follow started
+      Followers get(fn followers): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => T::BlockNumber;
+      // follower, followee => block the follow started
+      Following get(fn following): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => T::BlockNumber;
+      // account => handle and the deposit reserved for it
+      HandleOf get(fn handle_of): map hasher(blake2_128_concat) T::AccountId => Option<(Vec<u8>, DepositBalanceOf<T>)>;
+      AccountOfHandle get(fn account_of_handle): map hasher(blake2_128_concat) Vec<u8> => Option<T::AccountId>;
+      // holder of a handle => account it offered the handle to, see `accept_handle`
+      HandleOffer get(fn handle_offer): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+      // block at which the account created its profile
+      JoinedAt get(fn joined_at): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+      SignupBonusPaid get(fn signup_bonus_paid): map hasher(blake2_128_concat) T::AccountId => bool;
+      // account => era and the rewards earned in it
+      RewardedInEra get(fn rewarded_in_era): map hasher(blake2_128_concat) T::AccountId => (T::BlockNumber, T::TokenBalance);
+      // epoch, photo => unique likes received during the epoch, removed once paid out
+      EpochScores get(fn epoch_scores): double_map hasher(twox_64_concat) T::BlockNumber, hasher(blake2_128_concat) Vec<u8> => u32;
+      EpochTotalScore get(fn epoch_total_score): map hasher(twox_64_concat) T::BlockNumber => u32;
+      // oldest epoch whose rewards haven't been fully paid out
+      PayoutEpoch get(fn payout_epoch): T::BlockNumber;
+      // photo => trending score and the block it was last brought up to date
+      TrendingScores get(fn trending_scores): map hasher(blake2_128_concat) Vec<u8> => (u64, T::BlockNumber);
+      // top photos by trending score, highest first, all scores as of `TrendingUpdatedAt`
+      Trending get(fn trending): Vec<(Vec<u8>, u64)>;
+      TrendingUpdatedAt get(fn trending_updated_at): T::BlockNumber;
+      // cumulative tips received by a photo and by a creator
+      PhotoTips get(fn photo_tips): map hasher(blake2_128_concat) Vec<u8> => T::TokenBalance;
+      CreatorTips get(fn creator_tips): map hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+      // accounts allowed to hide and restore content, managed by root
+      Moderators get(fn is_moderator): map hasher(blake2_128_concat) T::AccountId => bool;
+      Reports get(fn reports): map hasher(twox_64_concat) ReportId => Option<Report<T::AccountId, T::BlockNumber>>;
+      NextReportId get(fn next_report_id): ReportId;
+      // open and appealed reports, so `pending_reports` doesn't go through resolved ones
+      PendingReports get(fn is_pending_report): map hasher(twox_64_concat) ReportId => bool;
+      // tokens staked to be drawn as a juror
+      JurorStakes get(fn juror_stakes): map hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+      JurorPool get(fn juror_pool): Vec<T::AccountId>;
+      Disputes get(fn disputes): map hasher(twox_64_concat) DisputeId => Option<Dispute<T::AccountId, T::Hash, T::BlockNumber, T::TokenBalance>>;
+      NextDisputeId get(fn next_dispute_id): DisputeId;
+      // private photo, account => content key wrapped for that account
+      AccessKeys get(fn access_key): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) T::AccountId => Option<Vec<u8>>;
+      // creator, tier id => tier
+      SubscriptionTiers get(fn subscription_tiers): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) TierId => Option<SubscriptionTier<T::TokenBalance>>;
+      // subscriber, creator => subscription
+      Subscriptions get(fn subscriptions): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => Option<Subscription<T::TokenBalance, T::BlockNumber>>;
+      // block => (subscriber, creator) subscriptions to renew at that block
+      RenewalsAt get(fn renewals_at): map hasher(twox_64_concat) T::BlockNumber => Vec<(T::AccountId, T::AccountId)>;
+      // first block of `RenewalsAt` not charged yet, it lags behind while transfers are paused
+      RenewalCursor get(fn renewal_cursor): T::BlockNumber;
+      // photo => its unresolved dispute
+      DisputeOf get(fn dispute_of): map hasher(blake2_128_concat) Vec<u8> => Option<DisputeId>;
+      Airdrops get(fn airdrops): map hasher(twox_64_concat) AirdropId => Option<Airdrop<T::AccountId, T::Hash, T::TokenBalance>>;
+      NextAirdropId get(fn next_airdrop_id): AirdropId;
+      AirdropClaimed get(fn airdrop_claimed): double_map hasher(twox_64_concat) AirdropId, hasher(blake2_128_concat) T::AccountId => bool;
   }
 }
 
@@ -97,13 +514,93 @@ decl_event!(
         AccountId = <T as system::Trait>::AccountId,
         Balance = <T as self::Trait>::TokenBalance,
     {
+        // event when a token is created
+        // tokenid, issuer, total supply
+        TokenCreated(TokenId, AccountId, Balance),
+        TokenMetadataUpdated(TokenId),
+        AccountFrozen(AccountId),
+        AccountThawed(AccountId),
+        TransfersPaused,
+        TransfersUnpaused,
         // event for transfer of tokens
         // tokenid, from, to, value
-        Transfer(AccountId, AccountId, Balance),
+        Transfer(TokenId, AccountId, AccountId, Balance),
         // event when an approval is made
         // tokenid, owner, spender, value
-        Approval(AccountId, AccountId, Balance),
+        Approval(TokenId, AccountId, AccountId, Balance),
+        // event when tokens are destroyed
+        // tokenid, holder, value
+        Burned(TokenId, AccountId, Balance),
+        // tokenid, sender, transfers made, transfers skipped
+        BatchTransferred(TokenId, AccountId, u32, u32),
+        // airdrop id, funder, tokens escrowed
+        AirdropCreated(AirdropId, AccountId, Balance),
+        // airdrop id, claimant, value
+        AirdropClaimed(AirdropId, AccountId, Balance),
+        // airdrop id, tokens returned to the funder
+        AirdropClosed(AirdropId, Balance),
+        // account, name, avatar
         AccountUpdated(AccountId, Vec<u8>, Vec<u8>),
+        // owner, photo
+        PhotoDeleted(AccountId, Vec<u8>),
+        // mentioned account, author, photo, index of the comment or `None` for the photo itself
+        // indexed under the topic `T::Hashing::hash_of(&mentioned_account)`
+        Mentioned(AccountId, AccountId, Vec<u8>, Option<u32>),
+        // photo, account
+        AccessGranted(Vec<u8>, AccountId),
+        AccessRevoked(Vec<u8>, AccountId),
+        // tipper, photo owner, photo, value, message
+        Tipped(AccountId, AccountId, Vec<u8>, Balance, Option<Vec<u8>>),
+        ModeratorAdded(AccountId),
+        ModeratorRemoved(AccountId),
+        // report id, reporter
+        Reported(ReportId, AccountId),
+        // report id, moderator, whether the content is now hidden
+        Moderated(ReportId, AccountId, bool),
+        // report id, moderator, the reported content was already deleted
+        ReportClosed(ReportId, AccountId),
+        // report id, content owner
+        Appealed(ReportId, AccountId),
+        // creator, tier id
+        TierUpdated(AccountId, TierId),
+        TierRemoved(AccountId, TierId),
+        // subscriber, creator, tier id
+        Subscribed(AccountId, AccountId, TierId),
+        // subscriber, creator, value charged
+        SubscriptionRenewed(AccountId, AccountId, Balance),
+        // subscriber, creator
+        SubscriptionCancelled(AccountId, AccountId),
+        SubscriptionLapsed(AccountId, AccountId),
+        // account, tokens still locked
+        Vested(AccountId, Balance),
+        JurorStaked(AccountId, Balance),
+        JurorUnstaked(AccountId, Balance),
+        // dispute id, opener, photo
+        DisputeOpened(DisputeId, AccountId, Vec<u8>),
+        // dispute id, juror
+        VoteCommitted(DisputeId, AccountId),
+        VoteRevealed(DisputeId, AccountId),
+        // dispute id, whether the photo is now hidden
+        DisputeResolved(DisputeId, bool),
+        // account, reward
+        Rewarded(AccountId, Balance),
+        // social tokens charged per unit of native fee, `None` if fees can't be paid in tokens
+        FeeRateUpdated(Option<FixedU128>),
+        // account, reward that was not minted, reason
+        RewardWithheld(AccountId, Balance, WithheldReason),
+        // owner, album id
+        AlbumCreated(AccountId, AlbumId),
+        AlbumUpdated(AccountId, AlbumId),
+        AlbumDeleted(AccountId, AlbumId),
+        // follower, followee
+        Followed(AccountId, AccountId),
+        Unfollowed(AccountId, AccountId),
+        // owner, handle
+        HandleClaimed(AccountId, Vec<u8>),
+        HandleReleased(AccountId, Vec<u8>),
+        // from, to, handle
+        HandleOffered(AccountId, AccountId, Vec<u8>),
+        HandleTransferred(AccountId, AccountId, Vec<u8>),
     }
 );
 
@@ -111,33 +608,129 @@ decl_event!(
 decl_module! {
     /// The module declaration.
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const HandleDeposit: DepositBalanceOf<T> = T::HandleDeposit::get();
+        const DepositPerByte: DepositBalanceOf<T> = T::DepositPerByte::get();
+        const CommentDeposit: T::TokenBalance = T::CommentDeposit::get();
+        const RewardEraLength: T::BlockNumber = T::RewardEraLength::get();
+        const MaxRewardPerEra: T::TokenBalance = T::MaxRewardPerEra::get();
+        const MinAccountAge: T::BlockNumber = T::MinAccountAge::get();
+        const RewardEpochLength: T::BlockNumber = T::RewardEpochLength::get();
+        const RewardPool: T::TokenBalance = T::RewardPool::get();
+        const TrendingDecay: Perbill = T::TrendingDecay::get();
+        const TrendingSize: u32 = T::TrendingSize::get();
+        const JurySize: u32 = T::JurySize::get();
+        const JuryStake: T::TokenBalance = T::JuryStake::get();
+        const CommitPeriod: T::BlockNumber = T::CommitPeriod::get();
+        const RevealPeriod: T::BlockNumber = T::RevealPeriod::get();
+        const SubscriptionPeriod: T::BlockNumber = T::SubscriptionPeriod::get();
+        const SocialTokenDecimals: u8 = T::SocialTokenDecimals::get();
+        const SocialTokenTicker: Vec<u8> = T::SocialTokenTicker::get();
+
         // initialize the default event for this module
         fn deposit_event() = default;
 
-        // initializes a new token
-        // generates an integer token_id so that all tokens are unique
+        // moves the storage of chains from the first release to the current layout
+        fn on_runtime_upgrade() -> Weight {
+            if <StorageVersion>::get() != Releases::V1 {
+                return T::DbWeight::get().reads(1);
+            }
+            let weight = migration::migrate::<T>();
+            <StorageVersion>::put(Releases::V2);
+            weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        }
+
+        // charges the subscriptions due at this block and pays out finished reward epochs
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::_charge_renewals(n).saturating_add(Self::_pay_epoch_rewards())
+        }
+
+        // initializes the social token under `SOCIAL_TOKEN`
         // takes a name, ticker, total supply for the token
-        // makes the initiating account the owner of the token
+        // the ticker must be `SocialTokenTicker` so the chain properties match the token
+        // makes the initiating account the owner of the token and the treasury
         // the balance of the owner is set to total supply
         #[weight = 10_000]
         fn init(origin, name: Vec<u8>, ticker: Vec<u8>, total_supply: T::TokenBalance) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
-            // checking max size for name and ticker
-            // byte arrays (vecs) with no max size should be avoided
             ensure!(!Self::initialized(), "already initialized");
-            ensure!(name.len() <= 64, "token name cannot exceed 64 bytes");
-            ensure!(ticker.len() <= 32, "token ticker cannot exceed 32 bytes");
-
-            let token = Erc20Token {
-                name,
-                ticker,
-                total_supply,
-            };
+            ensure!(ticker == T::SocialTokenTicker::get(), "ticker must be the social token ticker");
+            Self::_create_token(SOCIAL_TOKEN, sender.clone(), name, ticker, T::SocialTokenDecimals::get(), total_supply)?;
             <Initialized>::put(true);
-            <Treasury<T>>::put(sender.clone());
-            <TokenInfo<T>>::put(token);
-            <BalanceOf<T>>::insert(sender, total_supply);
+            <Treasury<T>>::put(sender);
+
+            Ok(())
+        }
+
+        // stops an account from sending or receiving tokens, callable by root or the treasury
+        #[weight = 10_000]
+        pub fn freeze_account(origin, who: T::AccountId) -> DispatchResult {
+            Self::_ensure_admin(origin)?;
+            <Frozen<T>>::insert(who.clone(), true);
+            Self::deposit_event(RawEvent::AccountFrozen(who));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn thaw_account(origin, who: T::AccountId) -> DispatchResult {
+            Self::_ensure_admin(origin)?;
+            ensure!(Self::frozen(who.clone()), "Account is not frozen");
+            <Frozen<T>>::remove(who.clone());
+            Self::deposit_event(RawEvent::AccountThawed(who));
+            Ok(())
+        }
+
+        // blocks every token transfer until `unpause`, callable by root or the treasury
+        #[weight = 10_000]
+        pub fn pause(origin) -> DispatchResult {
+            Self::_ensure_admin(origin)?;
+            <Paused>::put(true);
+            Self::deposit_event(RawEvent::TransfersPaused);
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn unpause(origin) -> DispatchResult {
+            Self::_ensure_admin(origin)?;
+            <Paused>::put(false);
+            Self::deposit_event(RawEvent::TransfersUnpaused);
+            Ok(())
+        }
+
+        // sets the wallet facing metadata of a token, callable by root or the treasury
+        #[weight = 10_000]
+        pub fn update_token_metadata(origin, token_id: TokenId, decimals: u8, icon: Vec<u8>, description: Vec<u8>) -> DispatchResult {
+            Self::_ensure_admin(origin)?;
+            ensure!(<TokenInfo<T>>::contains_key(token_id), "Token doesn't exist");
+            ensure!(decimals <= MAX_TOKEN_DECIMALS, "Too many decimals");
+            // the chain properties advertise the social token's decimals
+            ensure!(
+                token_id != SOCIAL_TOKEN || decimals == T::SocialTokenDecimals::get(),
+                "Social token decimals are fixed"
+            );
+            ensure!(icon.len() <= MAX_TOKEN_ICON_LEN, "Icon is too long");
+            ensure!(description.len() <= MAX_TOKEN_DESCRIPTION_LEN, "Description is too long");
+
+            <TokenInfo<T>>::mutate(token_id, |token| {
+                token.decimals = decimals;
+                token.icon = icon;
+                token.description = description;
+            });
+            Self::deposit_event(RawEvent::TokenMetadataUpdated(token_id));
+            Ok(())
+        }
+
+        // creates a new token, e.g. a brand's loyalty token for affiliate campaigns
+        // generates an integer token_id so that all tokens are unique
+        // the balance of the creator is set to total supply
+        #[weight = 10_000]
+        fn create_token(origin, name: Vec<u8>, ticker: Vec<u8>, total_supply: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let token_id = Self::next_token_id();
+            let next_id = token_id.checked_add(1).ok_or("overflow in calculating token id")?;
+            Self::_create_token(token_id, sender, name, ticker, 0, total_supply)?;
+            <NextTokenId>::put(next_id);
 
             Ok(())
         }
@@ -145,85 +738,360 @@ decl_module! {
         // transfer tokens from one account to another
         // origin is assumed as sender
         #[weight = 10_000]
-        fn transfer(_origin, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        fn transfer(_origin, token_id: TokenId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
-            Self::_transfer(sender, to, value)
+            Self::_transfer(token_id, sender, to, value)
         }
 
         // approve token transfer from one account to another
         // once this is done, transfer_from can be called with corresponding values
         #[weight = 10_000]
-        fn approve(_origin, spender: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        fn approve(_origin, token_id: TokenId, spender: T::AccountId, value: T::TokenBalance) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
-            Self::_approve(sender, spender, value)
+            Self::_approve(token_id, sender, spender, value)
         }
 
         // the ERC20 standard transfer_from function
         // implemented in the open-zeppelin way - increase/decrease allownace
-        // if approved, transfer from an account to another account without owner's signature
+        // if approved, the signer (the spender) transfers from an account to another account
+        // without owner's signature
         #[weight = 10_000]
-        pub fn transfer_from(_origin, from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
-            ensure!(<Allowance<T>>::contains_key((from.clone(), to.clone())), "Allowance does not exist.");
-            let allowance = Self::allowance((from.clone(), to.clone()));
-            ensure!(allowance >= value, "Not enough allowance.");
+        pub fn transfer_from(_origin, token_id: TokenId, from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+            let spender = ensure_signed(_origin)?;
+            ensure!(<Allowance<T>>::contains_key(token_id, (from.clone(), spender.clone())), "Allowance does not exist.");
+            Self::_spend_allowance(token_id, from.clone(), spender.clone(), to, value)?;
 
-            // using checked_sub (safe math) to avoid overflow
-            let updated_allowance = allowance.checked_sub(&value).ok_or("overflow in calculating allowance")?;
-            <Allowance<T>>::insert((from.clone(), to.clone()), updated_allowance);
+            Self::deposit_event(RawEvent::Approval(token_id, from, spender, value));
+            Ok(())
+        }
+
+        // pays many accounts at once, either all transfers go through or the call fails
+        // if `all_or_nothing`, otherwise the ones that can't be made are skipped
+        #[weight = 10_000 + 5_000 * transfers.len() as Weight]
+        pub fn batch_transfer(_origin, token_id: TokenId, transfers: Vec<(T::AccountId, T::TokenBalance)>, all_or_nothing: bool) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(transfers.len() <= MAX_BATCH_TRANSFERS, "Too many transfers");
+
+            if all_or_nothing {
+                // checking every transfer up front, so none fails halfway through the batch
+                ensure!(!Self::paused(), "Transfers are paused");
+                ensure!(!Self::frozen(sender.clone()), "Account is frozen");
+                ensure!(<BalanceOf<T>>::contains_key(token_id, sender.clone()), "Account does not own this token");
+                let mut total: T::TokenBalance = Zero::zero();
+                // balances of the recipients once they got all their transfers
+                let mut received: BTreeMap<T::AccountId, T::TokenBalance> = BTreeMap::new();
+                for (to, value) in transfers.iter() {
+                    ensure!(*to != sender, "Can't transfer to yourself");
+                    ensure!(!Self::frozen(to.clone()), "Account is frozen");
+                    total = total.checked_add(value).ok_or("overflow in calculating balance")?;
+                    let balance = received.entry(to.clone()).or_insert_with(|| Self::balance_of(token_id, to.clone()));
+                    *balance = balance.checked_add(value).ok_or("overflow in calculating balance")?;
+                }
+                let remaining = Self::balance_of(token_id, sender.clone()).checked_sub(&total).ok_or("Not enough balance.")?;
+                Self::_ensure_unlocked(token_id, &sender, remaining)?;
+            }
+
+            let mut made: u32 = 0;
+            let mut skipped: u32 = 0;
+            for (to, value) in transfers {
+                let result = if to == sender {
+                    Err("Can't transfer to yourself".into())
+                } else {
+                    Self::_transfer(token_id, sender.clone(), to, value)
+                };
+                match result {
+                    Ok(()) => made += 1,
+                    Err(e) if all_or_nothing => return Err(e),
+                    Err(_) => skipped += 1,
+                }
+            }
+            Self::deposit_event(RawEvent::BatchTransferred(token_id, sender, made, skipped));
+            Ok(())
+        }
+
+        // escrows `total` tokens in the airdrop's account for the accounts in the tree under `merkle_root`
+        #[weight = 10_000]
+        pub fn create_airdrop(_origin, token_id: TokenId, merkle_root: T::Hash, total: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(Self::initialized(), "Token is not initialized");
+            let airdrop_id = Self::next_airdrop_id();
+            let next_id = airdrop_id.checked_add(1).ok_or("overflow in calculating airdrop id")?;
+
+            Self::_transfer(token_id, sender.clone(), Self::_airdrop_account(airdrop_id), total)?;
+            <Airdrops<T>>::insert(airdrop_id, Airdrop { token_id, funder: sender.clone(), merkle_root, remaining: total });
+            <NextAirdropId>::put(next_id);
+
+            Self::deposit_event(RawEvent::AirdropCreated(airdrop_id, sender, total));
+            Ok(())
+        }
+
+        // claims the sender's `amount` with the sibling hashes from its leaf up to the root
+        #[weight = 10_000 + 1_000 * proof.len() as Weight]
+        pub fn claim_airdrop(_origin, airdrop_id: AirdropId, amount: T::TokenBalance, proof: Vec<T::Hash>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(proof.len() <= MAX_PROOF_LEN, "Proof is too long");
+            let mut airdrop = Self::airdrops(airdrop_id).ok_or("Airdrop doesn't exist")?;
+            ensure!(!Self::airdrop_claimed(airdrop_id, sender.clone()), "Airdrop already claimed");
 
-            Self::deposit_event(RawEvent::Approval(from.clone(), to.clone(), value));
-            Self::_transfer(from, to, value)
+            let leaf = T::Hashing::hash_of(&(sender.clone(), amount));
+            let root = proof.iter().fold(leaf, |node, sibling| {
+                if node <= *sibling {
+                    T::Hashing::hash_of(&(node, *sibling))
+                } else {
+                    T::Hashing::hash_of(&(*sibling, node))
+                }
+            });
+            ensure!(root == airdrop.merkle_root, "Invalid proof");
+
+            airdrop.remaining = airdrop.remaining.checked_sub(&amount).ok_or("Airdrop is exhausted")?;
+            Self::_transfer(airdrop.token_id, Self::_airdrop_account(airdrop_id), sender.clone(), amount)?;
+            <AirdropClaimed<T>>::insert(airdrop_id, sender.clone(), true);
+            <Airdrops<T>>::insert(airdrop_id, airdrop);
+
+            Self::deposit_event(RawEvent::AirdropClaimed(airdrop_id, sender, amount));
+            Ok(())
         }
 
+        // ends an airdrop and returns the unclaimed tokens to the funder
         #[weight = 10_000]
-        pub fn update_user(_origin, name: Vec<u8>, avatar: Vec<u8>) -> DispatchResult {
+        pub fn close_airdrop(_origin, airdrop_id: AirdropId) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
+            let airdrop = Self::airdrops(airdrop_id).ok_or("Airdrop doesn't exist")?;
+            ensure!(airdrop.funder == sender, "Only the funder can close an airdrop");
 
-            if ! <Accounts<T>>::contains_key(sender.clone()) {
-                Self::_credit(sender.clone(), 100.into())?;
+            if !airdrop.remaining.is_zero() {
+                Self::_transfer(airdrop.token_id, Self::_airdrop_account(airdrop_id), sender, airdrop.remaining)?;
             }
-            <Accounts<T>>::insert(sender, AccountProfile {name, avatar, photos: Vec::new()});
+            <Airdrops<T>>::remove(airdrop_id);
+            <AirdropClaimed<T>>::remove_prefix(airdrop_id);
+
+            Self::deposit_event(RawEvent::AirdropClosed(airdrop_id, airdrop.remaining));
             Ok(())
         }
 
+        // destroys tokens held by the sender, reducing the total supply
+        #[weight = 10_000]
+        pub fn burn(_origin, token_id: TokenId, value: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_burn(token_id, sender, value)
+        }
+
+        // destroys tokens of `from` out of the allowance `from` approved to the sender
         #[weight = 10_000]
-        pub fn upload_photo(_origin, photo: Vec<u8>, affiliate_url: Option<Vec<u8>>) -> DispatchResult {
+        pub fn burn_from(_origin, token_id: TokenId, from: T::AccountId, value: T::TokenBalance) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
+            ensure!(<Allowance<T>>::contains_key(token_id, (from.clone(), sender.clone())), "Allowance does not exist.");
+            let allowance = Self::allowance(token_id, (from.clone(), sender.clone()));
+            ensure!(allowance >= value, "Not enough allowance.");
 
-            ensure!(!<Photos<T>>::contains_key(photo.clone()), "This photo already uploaded");
-            // TODO: off chain verify this is actually exist in ipfs, and it's a photo.
+            let updated_allowance = allowance.checked_sub(&value).ok_or("overflow in calculating allowance")?;
+            Self::_burn(token_id, from.clone(), value)?;
+            <Allowance<T>>::insert(token_id, (from.clone(), sender.clone()), updated_allowance);
 
-            if let Some(affiliate_url) = affiliate_url {
-                <Photos<T>>::insert(photo.clone(), PhotoInfo { owner: sender.clone(), likes: Vec::new(), variants: Vec::new(), affiliate_url: Some(affiliate_url), comments: Vec::new()});
-            } else {
-                <Photos<T>>::insert(photo.clone(), PhotoInfo { owner: sender.clone(), likes: Vec::new(), variants: Vec::new(), affiliate_url: None, comments: Vec::new() });
+            Self::deposit_event(RawEvent::Approval(token_id, from, sender, value));
+            Ok(())
+        }
+
+        // creates or partially updates the sender's profile
+        // fields passed as `None` are left untouched, photos and follow counts are always kept
+        // feeless and rate limited by `LimitFreeActions`, the bio and links reserve `DepositPerByte`
+        // while the name and avatar are free so accounts with no balance can onboard
+        // the signup bonus is paid with the first update once the account is `MinAccountAge` old
+        #[weight = (10_000, Pays::No)]
+        pub fn update_user(_origin, name: Option<Vec<u8>>, avatar: Option<Vec<u8>>, bio: Option<Vec<u8>>, links: Option<Vec<Vec<u8>>>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+
+            if let Some(name) = &name {
+                ensure!(name.len() <= MAX_NAME_LEN, "name cannot exceed 64 bytes");
+            }
+            if let Some(avatar) = &avatar {
+                ensure!(avatar.len() <= MAX_AVATAR_LEN, "avatar cannot exceed 128 bytes");
+            }
+            if let Some(bio) = &bio {
+                ensure!(bio.len() <= MAX_BIO_LEN, "bio cannot exceed 512 bytes");
+            }
+            if let Some(links) = &links {
+                ensure!(links.len() <= MAX_LINKS, "too many links");
+                for link in links {
+                    ensure!(link.len() <= MAX_LINK_LEN, "link cannot exceed 256 bytes");
+                }
+            }
+
+            let mut profile = Self::accounts(sender.clone());
+            if let Some(name) = name {
+                profile.name = name;
+            }
+            if let Some(avatar) = avatar {
+                profile.avatar = avatar;
+            }
+            if let Some(bio) = bio {
+                profile.bio = bio;
+            }
+            if let Some(links) = links {
+                profile.links = links;
+            }
+            let bytes = profile.bio.len() + profile.links.iter().map(|l| l.len()).sum::<usize>();
+            profile.deposit = Self::_update_deposit(&sender, profile.deposit, bytes)?;
+
+            if ! <JoinedAt<T>>::contains_key(sender.clone()) {
+                <JoinedAt<T>>::insert(sender.clone(), <system::Module<T>>::block_number());
             }
-            let mut account = Self::accounts(sender.clone());
-            account.photos.push(photo);
-            <Accounts<T>>::insert(sender.clone(), account);
-            Self::_credit(sender, 10.into())
+            // waiting out the account age and going through the era cap keeps fresh accounts
+            // from farming the bonus with feeless calls
+            if !Self::signup_bonus_paid(sender.clone()) && Self::_old_enough(&sender) && !Self::frozen(sender.clone()) {
+                <SignupBonusPaid<T>>::insert(sender.clone(), true);
+                Self::_reward(sender.clone(), SIGNUP_BONUS.into())?;
+            }
+            <Accounts<T>>::insert(sender.clone(), profile.clone());
+
+            Self::deposit_event(RawEvent::AccountUpdated(sender, profile.name, profile.avatar));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn upload_photo(_origin, photo: Vec<u8>, affiliate_url: Option<Vec<u8>>, caption: Option<Vec<u8>>, tags: Vec<Vec<u8>>, mentions: Vec<T::AccountId>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let mentions = Self::_validate_mentions(mentions)?;
+            Self::_upload(sender.clone(), photo.clone(), affiliate_url, caption, tags, false)?;
+            Self::_notify_mentions(&sender, &photo, None, mentions);
+            Ok(())
         }
 
+        // uploads a photo only the owner and the accounts it grants access to can see
+        // `encrypted_cid` serves as the photo id, `wrapped_key` is the content key wrapped for the owner
         #[weight = 10_000]
+        pub fn upload_private_photo(_origin, encrypted_cid: Vec<u8>, wrapped_key: Vec<u8>, caption: Option<Vec<u8>>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(wrapped_key.len() <= MAX_WRAPPED_KEY_LEN, "wrapped key cannot exceed 256 bytes");
+            Self::_upload(sender.clone(), encrypted_cid.clone(), None, caption, Vec::new(), true)?;
+            <AccessKeys<T>>::insert(encrypted_cid, sender, wrapped_key);
+            Ok(())
+        }
+
+        // gives `account` the content key of a private photo, wrapped for it by the owner
+        #[weight = 10_000]
+        pub fn grant_access(_origin, photo: Vec<u8>, account: T::AccountId, wrapped_key: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            let photo_info = Self::photos(photo.clone());
+            ensure!(photo_info.owner == sender, "Not the owner of this photo");
+            ensure!(photo_info.private, "Photo is not private");
+            ensure!(wrapped_key.len() <= MAX_WRAPPED_KEY_LEN, "wrapped key cannot exceed 256 bytes");
+
+            <AccessKeys<T>>::insert(photo.clone(), account.clone(), wrapped_key);
+            Self::deposit_event(RawEvent::AccessGranted(photo, account));
+            Ok(())
+        }
+
+        // removes the key stored for `account`, re-encrypting the photo is up to the owner
+        #[weight = 10_000]
+        pub fn revoke_access(_origin, photo: Vec<u8>, account: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            ensure!(Self::photos(photo.clone()).owner == sender, "Not the owner of this photo");
+            ensure!(account != sender, "Cannot revoke the owner's access");
+            ensure!(<AccessKeys<T>>::contains_key(photo.clone(), account.clone()), "Account has no access");
+
+            <AccessKeys<T>>::remove(photo.clone(), account.clone());
+            Self::deposit_event(RawEvent::AccessRevoked(photo, account));
+            Ok(())
+        }
+
+        // feeless, rate limited by `LimitFreeActions`
+        #[weight = (10_000, Pays::No)]
         pub fn like_photo(_origin, photo: Vec<u8>) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
             ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
             let mut photo_info = Self::photos(photo.clone());
+            ensure!(!photo_info.hidden, "Photo is hidden");
+            Self::_ensure_can_view(&photo, &photo_info, &sender)?;
             for l in photo_info.clone().likes {
                 ensure!(l != sender, "Already liked");
             }
             photo_info.likes.push(sender.clone());
+            // self likes and likes from new accounts don't earn the owner anything
+            if photo_info.owner != sender && Self::_old_enough(&sender) {
+                let epoch = Self::_current_epoch();
+                <EpochScores<T>>::mutate(epoch, photo.clone(), |score| *score = score.saturating_add(1));
+                <EpochTotalScore<T>>::mutate(epoch, |total| *total = total.saturating_add(1));
+            }
             <Photos<T>>::insert(photo.clone(), photo_info);
-            Self::_credit(sender, 1.into())
+            Self::_bump_trending(&photo, TRENDING_LIKE_POINTS);
+            Ok(())
+        }
+
+        // feeless and rate limited by `LimitFreeActions`, reserves `CommentDeposit` social tokens
+        // from the sender instead of a native deposit
+        #[weight = (10_000, Pays::No)]
+        pub fn comment_photo(_origin, photo: Vec<u8>, comment: Vec<u8>, mentions: Vec<T::AccountId>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            ensure!(comment.len() <= MAX_COMMENT_LEN, "comment cannot exceed 512 bytes");
+            let mentions = Self::_validate_mentions(mentions)?;
+            let photo_info = Self::photos(photo.clone());
+            ensure!(!photo_info.hidden, "Photo is hidden");
+            Self::_ensure_can_view(&photo, &photo_info, &sender)?;
+            let count = Self::comment_count(photo.clone());
+            ensure!(count < MAX_COMMENTS_PER_PHOTO, "Too many comments");
+            let index = Self::next_comment_index(photo.clone());
+            let next_index = index.checked_add(1).ok_or("overflow in calculating comment index")?;
+
+            let deposit = T::CommentDeposit::get();
+            <Self as ReservableCurrency<_>>::reserve(&sender, deposit)?;
+            <Comments<T>>::insert(photo.clone(), index, Comment { author: sender.clone(), text: comment, deposit, hidden: false });
+            <NextCommentIndex>::insert(photo.clone(), next_index);
+            <CommentCount>::insert(photo.clone(), count + 1);
+            Self::_bump_trending(&photo, TRENDING_COMMENT_POINTS);
+            Self::_notify_mentions(&sender, &photo, Some(index), mentions);
+            Ok(())
         }
 
+        // removes one of the sender's own comments and returns its deposit
         #[weight = 10_000]
-        pub fn comment_photo(_origin, photo: Vec<u8>, comment: Vec<u8>) -> DispatchResult {
+        pub fn delete_comment(_origin, photo: Vec<u8>, index: u32) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
-            let mut photo_info = Self::photos(photo.clone());
-            photo_info.comments.push(comment);
-            <Photos<T>>::insert(photo.clone(), photo_info);
-            Self::_credit(sender, 1.into())
+            let comment = Self::comments(photo.clone(), index).ok_or("Comment doesn't exist")?;
+            ensure!(comment.author == sender, "Not the author of this comment");
+
+            <Self as ReservableCurrency<_>>::unreserve(&sender, comment.deposit);
+            <Comments<T>>::remove(photo.clone(), index);
+            <CommentCount>::mutate(photo, |count| *count = count.saturating_sub(1));
+            Ok(())
+        }
+
+        // removes a photo with its comments and index entries, returning every deposit held for it
+        #[weight = 10_000]
+        pub fn delete_photo(_origin, photo: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            let photo_info = Self::photos(photo.clone());
+            ensure!(photo_info.owner == sender, "Not the owner of this photo");
+            ensure!(!<DisputeOf>::contains_key(photo.clone()), "Photo is under dispute");
+
+            Self::_unindex_tags(&photo, &photo_info.tags);
+            <Accounts<T>>::mutate(sender.clone(), |profile| profile.photos.retain(|p| p != &photo));
+            let albums: Vec<(AlbumId, Album)> = <Albums<T>>::iter_prefix(sender.clone())
+                .filter(|(_, album)| album.photos.contains(&photo))
+                .collect();
+            for (album_id, mut album) in albums {
+                album.photos.retain(|p| p != &photo);
+                <Albums<T>>::insert(sender.clone(), album_id, album);
+            }
+            for (_, comment) in <Comments<T>>::iter_prefix(photo.clone()) {
+                <Self as ReservableCurrency<_>>::unreserve(&comment.author, comment.deposit);
+            }
+            <Comments<T>>::remove_prefix(photo.clone());
+            <NextCommentIndex>::remove(photo.clone());
+            <CommentCount>::remove(photo.clone());
+            T::Currency::unreserve(&sender, photo_info.deposit);
+            <Photos<T>>::remove(photo.clone());
+            <TrendingScores<T>>::remove(photo.clone());
+            <PhotoTips<T>>::remove(photo.clone());
+            <AccessKeys<T>>::remove_prefix(photo.clone());
+            <Trending>::mutate(|trending| trending.retain(|(p, _)| p != &photo));
+
+            Self::deposit_event(RawEvent::PhotoDeleted(sender, photo));
+            Ok(())
         }
 
         #[weight = 10_000]
@@ -231,81 +1099,1314 @@ decl_module! {
             let sender = ensure_signed(_origin)?;
             ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
             let mut photo_info = Self::photos(photo.clone());
+            Self::_ensure_can_view(&photo, &photo_info, &sender)?;
             for v in photo_info.clone().variants {
                 ensure!(v != updated_photo, "Already has this variant");
             }
             photo_info.variants.push(updated_photo);
             <Photos<T>>::insert(photo.clone(), photo_info);
-            Self::_credit(sender, 2.into())
+            Self::_bump_trending(&photo, TRENDING_VARIANT_POINTS);
+            Ok(())
         }
 
+        // sends tokens to the owner of a photo, with an optional message
         #[weight = 10_000]
-        pub fn create_affiliate(_origin, url: Vec<u8>, total_credit: T::TokenBalance, single_click_credit: T::TokenBalance, url_append: Vec<u8>) -> DispatchResult {
+        pub fn tip_photo(_origin, photo: Vec<u8>, value: T::TokenBalance, message: Option<Vec<u8>>) -> DispatchResult {
             let sender = ensure_signed(_origin)?;
-            Self::_approve(sender.clone(), Self::treasury(), total_credit)?;
-            let mut affiliations = <Affiliations<T>>::get(url.clone());
-            affiliations.push(AffiliateProvider { total_credit, single_click_credit, payer: sender.clone(), url_append });
-            <Affiliations<T>>::insert(url, affiliations);
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            if let Some(message) = &message {
+                ensure!(message.len() <= MAX_TIP_MESSAGE_LEN, "tip message cannot exceed 256 bytes");
+            }
+            let photo_info = Self::photos(photo.clone());
+            ensure!(!photo_info.hidden, "Photo is hidden");
+            Self::_ensure_can_view(&photo, &photo_info, &sender)?;
+            let owner = photo_info.owner;
+            ensure!(owner != sender, "Cannot tip your own photo");
+
+            let photo_tips = Self::photo_tips(photo.clone()).checked_add(&value).ok_or("overflow in calculating tips")?;
+            let creator_tips = Self::creator_tips(owner.clone()).checked_add(&value).ok_or("overflow in calculating tips")?;
+            Self::_transfer(SOCIAL_TOKEN, sender.clone(), owner.clone(), value)?;
+            <PhotoTips<T>>::insert(photo.clone(), photo_tips);
+            <CreatorTips<T>>::insert(owner.clone(), creator_tips);
+
+            Self::deposit_event(RawEvent::Tipped(sender, owner, photo, value, message));
             Ok(())
         }
 
+        // replaces the caption and tags of a photo, only the owner can do this
         #[weight = 10_000]
-        pub fn pay_affiliate(_origin, url: Vec<u8>, url_append: Vec<u8>, to: T::AccountId) -> DispatchResult {
-            let providers = Self::affiliations(url.clone());
-            for p in providers {
-                if p.url_append == url_append {
-                    return Ok(Self::transfer_from(_origin, p.payer, to, p.single_click_credit)?);
-                }
-            }
-            ensure!(false, "affiliation does not exist");
+        pub fn edit_photo_details(_origin, photo: Vec<u8>, caption: Option<Vec<u8>>, tags: Vec<Vec<u8>>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            let mut photo_info = Self::photos(photo.clone());
+            ensure!(photo_info.owner == sender, "Not the owner of this photo");
+            Self::_ensure_valid_caption(&caption)?;
+            let tags = Self::_normalize_tags(tags)?;
+            ensure!(!photo_info.private || tags.is_empty(), "Private photos can't be tagged");
+
+            Self::_unindex_tags(&photo, &photo_info.tags);
+            Self::_index_tags(&photo, &tags);
+            photo_info.caption = caption;
+            photo_info.tags = tags;
+            photo_info.deposit = Self::_update_deposit(&sender, photo_info.deposit, Self::_photo_bytes(&photo, &photo_info))?;
+            <Photos<T>>::insert(photo, photo_info);
             Ok(())
         }
-    }
-}
 
-// implementation of mudule
-// utility and private functions
-// if marked public, accessible by other modules
-impl<T: Trait> Module<T> {
-    // the ERC20 standard transfer function
-    // internal
-    fn _transfer(from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
-        ensure!(<BalanceOf<T>>::contains_key(from.clone()), "Account does not own this token");
-        let sender_balance = Self::balance_of(from.clone());
-        ensure!(sender_balance >= value, "Not enough balance.");
+        #[weight = 10_000]
+        pub fn create_album(_origin, name: Vec<u8>, public: bool) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(name.len() <= MAX_ALBUM_NAME_LEN, "album name cannot exceed 64 bytes");
+            let count = Self::album_count(sender.clone());
+            ensure!(count < MAX_ALBUMS, "Too many albums");
 
-        let updated_from_balance =
-            sender_balance.checked_sub(&value).ok_or("overflow in calculating balance")?;
-        let receiver_balance = Self::balance_of(to.clone());
-        let updated_to_balance =
-            receiver_balance.checked_add(&value).ok_or("overflow in calculating balance")?;
+            let album_id = Self::next_album_id(sender.clone());
+            let next_id = album_id.checked_add(1).ok_or("overflow in calculating album id")?;
+            <NextAlbumId<T>>::insert(sender.clone(), next_id);
+            <AlbumCount<T>>::insert(sender.clone(), count + 1);
+            <Albums<T>>::insert(sender.clone(), album_id, Album { name, photos: Vec::new(), public });
 
-        // reduce sender's balance
-        <BalanceOf<T>>::insert(from.clone(), updated_from_balance);
+            Self::deposit_event(RawEvent::AlbumCreated(sender, album_id));
+            Ok(())
+        }
 
-        // increase receiver's balance
-        <BalanceOf<T>>::insert(to.clone(), updated_to_balance);
+        #[weight = 10_000]
+        pub fn rename_album(_origin, album_id: AlbumId, name: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(name.len() <= MAX_ALBUM_NAME_LEN, "album name cannot exceed 64 bytes");
+            Self::_mutate_album(sender, album_id, |album| {
+                album.name = name;
+                Ok(())
+            })
+        }
 
-        Self::deposit_event(RawEvent::Transfer(from, to, value));
-        Ok(())
-    }
+        #[weight = 10_000]
+        pub fn set_album_visibility(_origin, album_id: AlbumId, public: bool) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_mutate_album(sender, album_id, |album| {
+                album.public = public;
+                Ok(())
+            })
+        }
 
-    fn _credit(to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
-        let receiver_balance = Self::balance_of(to.clone());
-        let updated_to_balance =
-            receiver_balance.checked_add(&value).ok_or("overflow in calculating balance")?;
-        <BalanceOf<T>>::insert(to.clone(), updated_to_balance);
+        // deleting an album leaves the photos themselves untouched
+        #[weight = 10_000]
+        pub fn delete_album(_origin, album_id: AlbumId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Albums<T>>::contains_key(sender.clone(), album_id), "Album doesn't exist");
+            <Albums<T>>::remove(sender.clone(), album_id);
+            <AlbumCount<T>>::mutate(sender.clone(), |count| *count = count.saturating_sub(1));
+            Self::deposit_event(RawEvent::AlbumDeleted(sender, album_id));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn add_to_album(_origin, album_id: AlbumId, photo: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            ensure!(Self::photos(photo.clone()).owner == sender, "Not the owner of this photo");
+            Self::_mutate_album(sender, album_id, |album| {
+                ensure!(!album.photos.contains(&photo), "Photo already in album");
+                ensure!(album.photos.len() < MAX_ALBUM_PHOTOS, "Album is full");
+                album.photos.push(photo);
+                Ok(())
+            })
+        }
+
+        #[weight = 10_000]
+        pub fn remove_from_album(_origin, album_id: AlbumId, photo: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_mutate_album(sender, album_id, |album| {
+                ensure!(album.photos.contains(&photo), "Photo not in album");
+                album.photos.retain(|p| p != &photo);
+                Ok(())
+            })
+        }
+
+        // moves a photo to `position` within the album, shifting the others
+        #[weight = 10_000]
+        pub fn move_in_album(_origin, album_id: AlbumId, photo: Vec<u8>, position: u32) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_mutate_album(sender, album_id, |album| {
+                let index = album.photos.iter().position(|p| p == &photo).ok_or("Photo not in album")?;
+                let position = position as usize;
+                ensure!(position < album.photos.len(), "Position out of range");
+                let photo = album.photos.remove(index);
+                album.photos.insert(position, photo);
+                Ok(())
+            })
+        }
+
+        #[weight = 10_000]
+        pub fn follow(_origin, account: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(sender != account, "Cannot follow yourself");
+            ensure!(<Accounts<T>>::contains_key(sender.clone()), "Account doesn't exist");
+            ensure!(<Accounts<T>>::contains_key(account.clone()), "Account doesn't exist");
+            ensure!(!<Followers<T>>::contains_key(account.clone(), sender.clone()), "Already following");
+
+            let now = <system::Module<T>>::block_number();
+            <Followers<T>>::insert(account.clone(), sender.clone(), now);
+            <Following<T>>::insert(sender.clone(), account.clone(), now);
+            <Accounts<T>>::mutate(account.clone(), |profile| profile.followers = profile.followers.saturating_add(1));
+            <Accounts<T>>::mutate(sender.clone(), |profile| profile.following = profile.following.saturating_add(1));
+
+            Self::deposit_event(RawEvent::Followed(sender, account));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn unfollow(_origin, account: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Followers<T>>::contains_key(account.clone(), sender.clone()), "Not following");
+
+            <Followers<T>>::remove(account.clone(), sender.clone());
+            <Following<T>>::remove(sender.clone(), account.clone());
+            <Accounts<T>>::mutate(account.clone(), |profile| profile.followers = profile.followers.saturating_sub(1));
+            <Accounts<T>>::mutate(sender.clone(), |profile| profile.following = profile.following.saturating_sub(1));
+
+            Self::deposit_event(RawEvent::Unfollowed(sender, account));
+            Ok(())
+        }
+
+        // claims a unique lowercase handle, reserving `HandleDeposit` until it is released
+        #[weight = 10_000]
+        pub fn claim_handle(_origin, handle: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_ensure_valid_handle(&handle)?;
+            ensure!(!<HandleOf<T>>::contains_key(sender.clone()), "Account already has a handle");
+            ensure!(!<AccountOfHandle<T>>::contains_key(handle.clone()), "Handle already taken");
+
+            let deposit = T::HandleDeposit::get();
+            T::Currency::reserve(&sender, deposit)?;
+            <HandleOf<T>>::insert(sender.clone(), (handle.clone(), deposit));
+            <AccountOfHandle<T>>::insert(handle.clone(), sender.clone());
+
+            Self::deposit_event(RawEvent::HandleClaimed(sender, handle));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn release_handle(_origin) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let (handle, deposit) = Self::handle_of(sender.clone()).ok_or("Account has no handle")?;
+
+            T::Currency::unreserve(&sender, deposit);
+            <HandleOf<T>>::remove(sender.clone());
+            <AccountOfHandle<T>>::remove(handle.clone());
+            <HandleOffer<T>>::remove(sender.clone());
+
+            Self::deposit_event(RawEvent::HandleReleased(sender, handle));
+            Ok(())
+        }
+
+        // offers the handle to `to`, which takes it over with `accept_handle`
+        // a new offer replaces the previous one
+        #[weight = 10_000]
+        pub fn transfer_handle(_origin, to: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let (handle, _) = Self::handle_of(sender.clone()).ok_or("Account has no handle")?;
+            ensure!(to != sender, "Cannot transfer a handle to yourself");
+
+            <HandleOffer<T>>::insert(sender.clone(), to.clone());
+            Self::deposit_event(RawEvent::HandleOffered(sender, to, handle));
+            Ok(())
+        }
+
+        // takes over the handle `from` offered to the sender, who must not have one yet and pays
+        // the deposit from then on
+        #[weight = 10_000]
+        pub fn accept_handle(_origin, from: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(Self::handle_offer(from.clone()) == Some(sender.clone()), "Handle was not offered to this account");
+            let (handle, deposit) = Self::handle_of(from.clone()).ok_or("Account has no handle")?;
+            ensure!(!<HandleOf<T>>::contains_key(sender.clone()), "Account already has a handle");
+
+            let new_deposit = T::HandleDeposit::get();
+            T::Currency::reserve(&sender, new_deposit)?;
+            T::Currency::unreserve(&from, deposit);
+            <HandleOffer<T>>::remove(from.clone());
+            <HandleOf<T>>::remove(from.clone());
+            <HandleOf<T>>::insert(sender.clone(), (handle.clone(), new_deposit));
+            <AccountOfHandle<T>>::insert(handle.clone(), sender.clone());
+
+            Self::deposit_event(RawEvent::HandleTransferred(from, sender, handle));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn add_moderator(origin, who: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+            <Moderators<T>>::insert(who.clone(), true);
+            Self::deposit_event(RawEvent::ModeratorAdded(who));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn remove_moderator(origin, who: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+            <Moderators<T>>::remove(who.clone());
+            Self::deposit_event(RawEvent::ModeratorRemoved(who));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn set_fee_rate(origin, rate: Option<FixedU128>) -> DispatchResult {
+            ensure_root(origin)?;
+            <FeeRate>::set(rate);
+            Self::deposit_event(RawEvent::FeeRateUpdated(rate));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn report_photo(_origin, photo: Vec<u8>, reason: ReportReason) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            Self::_report(sender, ContentRef::Photo(photo), reason)
+        }
+
+        #[weight = 10_000]
+        pub fn report_comment(_origin, photo: Vec<u8>, index: u32, reason: ReportReason) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Comments<T>>::contains_key(photo.clone(), index), "Comment doesn't exist");
+            Self::_report(sender, ContentRef::Comment(photo, index), reason)
+        }
+
+        // resolves an open or appealed report, hiding the content or leaving/restoring it
+        // reports about deleted content are closed without `hide` being looked at
+        #[weight = 10_000]
+        pub fn moderate(_origin, report_id: ReportId, hide: bool) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(Self::is_moderator(sender.clone()), "Not a moderator");
+            let mut report = Self::reports(report_id).ok_or("Report doesn't exist")?;
+            ensure!(
+                report.status == ReportStatus::Open || report.status == ReportStatus::Appealed,
+                "Report already resolved"
+            );
+
+            let exists = Self::_content_exists(&report.content);
+            if exists {
+                Self::_set_hidden(&report.content, hide)?;
+                report.status = if hide { ReportStatus::Upheld } else { ReportStatus::Dismissed };
+            } else {
+                report.status = ReportStatus::Closed;
+            }
+            <Reports<T>>::insert(report_id, report);
+            <PendingReports>::remove(report_id);
+
+            if exists {
+                Self::deposit_event(RawEvent::Moderated(report_id, sender, hide));
+            } else {
+                Self::deposit_event(RawEvent::ReportClosed(report_id, sender));
+            }
+            Ok(())
+        }
+
+        // lets the owner of hidden content ask a moderator to look at the report again
+        #[weight = 10_000]
+        pub fn appeal(_origin, report_id: ReportId, statement: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(statement.len() <= MAX_APPEAL_LEN, "appeal cannot exceed 512 bytes");
+            let mut report = Self::reports(report_id).ok_or("Report doesn't exist")?;
+            ensure!(report.status == ReportStatus::Upheld, "Only upheld reports can be appealed");
+            ensure!(report.appeal.is_none(), "Report already appealed");
+            ensure!(Self::_content_owner(&report.content)? == sender, "Not the owner of this content");
+
+            report.status = ReportStatus::Appealed;
+            report.appeal = Some(statement);
+            <Reports<T>>::insert(report_id, report);
+            <PendingReports>::insert(report_id, true);
+
+            Self::deposit_event(RawEvent::Appealed(report_id, sender));
+            Ok(())
+        }
+
+        // moves tokens into the juror stake, making the account eligible for juries
+        #[weight = 10_000]
+        pub fn stake_juror(_origin, value: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let stake = Self::juror_stakes(sender.clone()).checked_add(&value).ok_or("overflow in calculating stake")?;
+            let mut pool = Self::juror_pool();
+            if !pool.contains(&sender) {
+                ensure!(pool.len() < MAX_JUROR_POOL, "Juror pool is full");
+                pool.push(sender.clone());
+            }
+
+            Self::_debit(sender.clone(), value)?;
+            <JurorStakes<T>>::insert(sender.clone(), stake);
+            <JurorPool<T>>::put(pool);
+
+            Self::deposit_event(RawEvent::JurorStaked(sender, value));
+            Ok(())
+        }
+
+        // stakes escrowed in running disputes can't be withdrawn until they are resolved
+        #[weight = 10_000]
+        pub fn unstake_juror(_origin, value: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let stake = Self::juror_stakes(sender.clone());
+            ensure!(stake >= value, "Not enough stake.");
+
+            let updated_stake = stake.checked_sub(&value).ok_or("overflow in calculating stake")?;
+            // credited first, a frozen juror keeps the stake
+            Self::_credit(sender.clone(), value)?;
+            if updated_stake.is_zero() {
+                <JurorStakes<T>>::remove(sender.clone());
+                <JurorPool<T>>::mutate(|pool| pool.retain(|j| j != &sender));
+            } else {
+                <JurorStakes<T>>::insert(sender.clone(), updated_stake);
+            }
+
+            Self::deposit_event(RawEvent::JurorUnstaked(sender, value));
+            Ok(())
+        }
+
+        // asks a jury to set the photo's visibility to `hide`, escrowing `JuryStake` from the sender
+        #[weight = 10_000]
+        pub fn open_dispute(_origin, photo: Vec<u8>, hide: bool) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Photos<T>>::contains_key(photo.clone()), "Photo doesn't exist");
+            let photo_info = Self::photos(photo.clone());
+            ensure!(photo_info.hidden != hide, "Photo already has this visibility");
+            ensure!(!<DisputeOf>::contains_key(photo.clone()), "Photo already disputed");
+
+            let dispute_id = Self::next_dispute_id();
+            let next_id = dispute_id.checked_add(1).ok_or("overflow in calculating dispute id")?;
+            let stake = T::JuryStake::get();
+            let jurors = Self::_draw_jury(dispute_id, &[sender.clone(), photo_info.owner], stake)?;
+
+            Self::_debit(sender.clone(), stake)?;
+            for juror in jurors.iter() {
+                <JurorStakes<T>>::mutate(juror, |s| *s = s.saturating_sub(stake));
+            }
+            let now = <system::Module<T>>::block_number();
+            let commit_end = now.saturating_add(T::CommitPeriod::get());
+            let reveal_end = commit_end.saturating_add(T::RevealPeriod::get());
+            <NextDisputeId>::put(next_id);
+            <DisputeOf>::insert(photo.clone(), dispute_id);
+            <Disputes<T>>::insert(dispute_id, Dispute {
+                photo: photo.clone(),
+                opener: sender.clone(),
+                hide,
+                jurors,
+                commits: Vec::new(),
+                votes: Vec::new(),
+                commit_end,
+                reveal_end,
+                stake,
+            });
+
+            Self::deposit_event(RawEvent::DisputeOpened(dispute_id, sender, photo));
+            Ok(())
+        }
+
+        // `commitment` is the hash of the encoded `(juror, dispute_id, hide, salt)` that will be revealed,
+        // binding it to the juror and the dispute so it can't be copied from another commit
+        #[weight = 10_000]
+        pub fn commit_vote(_origin, dispute_id: DisputeId, commitment: T::Hash) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let mut dispute = Self::disputes(dispute_id).ok_or("Dispute doesn't exist")?;
+            ensure!(dispute.jurors.contains(&sender), "Not a juror of this dispute");
+            ensure!(<system::Module<T>>::block_number() <= dispute.commit_end, "Commit period is over");
+            ensure!(!dispute.commits.iter().any(|(j, _)| j == &sender), "Already committed");
+
+            dispute.commits.push((sender.clone(), commitment));
+            <Disputes<T>>::insert(dispute_id, dispute);
+
+            Self::deposit_event(RawEvent::VoteCommitted(dispute_id, sender));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn reveal_vote(_origin, dispute_id: DisputeId, hide: bool, salt: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let mut dispute = Self::disputes(dispute_id).ok_or("Dispute doesn't exist")?;
+            let now = <system::Module<T>>::block_number();
+            ensure!(now > dispute.commit_end, "Commit period is not over");
+            ensure!(now <= dispute.reveal_end, "Reveal period is over");
+            let commitment = dispute.commits.iter()
+                .find(|(j, _)| j == &sender)
+                .map(|(_, c)| *c)
+                .ok_or("No committed vote")?;
+            ensure!(!dispute.votes.iter().any(|(j, _)| j == &sender), "Already revealed");
+            ensure!(
+                T::Hashing::hash_of(&(sender.clone(), dispute_id, hide, salt)) == commitment,
+                "Vote doesn't match commitment"
+            );
+
+            dispute.votes.push((sender.clone(), hide));
+            <Disputes<T>>::insert(dispute_id, dispute);
+
+            Self::deposit_event(RawEvent::VoteRevealed(dispute_id, sender));
+            Ok(())
+        }
+
+        // settles a dispute once its reveal period is over, anyone can call this
+        // the majority decides the visibility, jurors who voted otherwise or didn't reveal
+        // lose their stake (and so does the opener if the jury disagrees) to the majority
+        // on a tie nothing changes and all stakes are returned
+        #[weight = 10_000]
+        pub fn resolve_dispute(_origin, dispute_id: DisputeId) -> DispatchResult {
+            ensure_signed(_origin)?;
+            let dispute = Self::disputes(dispute_id).ok_or("Dispute doesn't exist")?;
+            ensure!(<system::Module<T>>::block_number() > dispute.reveal_end, "Reveal period is not over");
+
+            let hide_votes = dispute.votes.iter().filter(|(_, v)| *v).count();
+            let keep_votes = dispute.votes.len() - hide_votes;
+            let stake = dispute.stake;
+            let mut hidden = Self::photos(dispute.photo.clone()).hidden;
+
+            // the opener's refund comes first, nothing is written if it fails
+            if hide_votes == keep_votes {
+                Self::_return_escrow(dispute.opener.clone(), stake)?;
+                for juror in dispute.jurors.iter() {
+                    <JurorStakes<T>>::mutate(juror, |s| *s = s.saturating_add(stake));
+                }
+            } else {
+                let outcome = hide_votes > keep_votes;
+                let winners: Vec<T::AccountId> = dispute.votes.iter()
+                    .filter(|(_, v)| *v == outcome)
+                    .map(|(j, _)| j.clone())
+                    .collect();
+                let mut losers = dispute.jurors.len() - winners.len();
+                if outcome == dispute.hide {
+                    Self::_return_escrow(dispute.opener.clone(), stake)?;
+                } else {
+                    losers += 1;
+                }
+                let pot = stake.saturating_mul((losers as u32).into());
+                let winner_count: T::TokenBalance = (winners.len() as u32).into();
+                let share = pot / winner_count;
+                // what doesn't divide evenly goes to the first juror to reveal a winning vote
+                let mut remainder = pot.saturating_sub(share.saturating_mul(winner_count));
+                for juror in winners.iter() {
+                    let payout = stake.saturating_add(share).saturating_add(remainder);
+                    <JurorStakes<T>>::mutate(juror, |s| *s = s.saturating_add(payout));
+                    remainder = Zero::zero();
+                }
+                if <Photos<T>>::contains_key(dispute.photo.clone()) {
+                    <Photos<T>>::mutate(dispute.photo.clone(), |info| info.hidden = outcome);
+                }
+                hidden = outcome;
+            }
+            <DisputeOf>::remove(dispute.photo);
+            <Disputes<T>>::remove(dispute_id);
+
+            Self::deposit_event(RawEvent::DisputeResolved(dispute_id, hidden));
+            Ok(())
+        }
+
+        // creates or updates one of the sender's subscription tiers
+        #[weight = 10_000]
+        pub fn set_subscription_tier(_origin, tier: TierId, name: Vec<u8>, price: T::TokenBalance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(name.len() <= MAX_TIER_NAME_LEN, "tier name cannot exceed 64 bytes");
+            <SubscriptionTiers<T>>::insert(sender.clone(), tier, SubscriptionTier { name, price });
+            Self::deposit_event(RawEvent::TierUpdated(sender, tier));
+            Ok(())
+        }
+
+        // subscriptions to a removed tier lapse at their next renewal
+        #[weight = 10_000]
+        pub fn remove_subscription_tier(_origin, tier: TierId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<SubscriptionTiers<T>>::contains_key(sender.clone(), tier), "Tier doesn't exist");
+            <SubscriptionTiers<T>>::remove(sender.clone(), tier);
+            Self::deposit_event(RawEvent::TierRemoved(sender, tier));
+            Ok(())
+        }
+
+        // pays the first period right away, later periods are charged in `on_initialize`
+        // from the allowance the sender approves to the creator
+        #[weight = 10_000]
+        pub fn subscribe(_origin, creator: T::AccountId, tier: TierId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(sender != creator, "Cannot subscribe to yourself");
+            ensure!(!<Subscriptions<T>>::contains_key(sender.clone(), creator.clone()), "Already subscribed");
+            let price = Self::subscription_tiers(creator.clone(), tier).ok_or("Tier doesn't exist")?.price;
+
+            Self::_transfer(SOCIAL_TOKEN, sender.clone(), creator.clone(), price)?;
+            let paid_until = <system::Module<T>>::block_number().saturating_add(T::SubscriptionPeriod::get());
+            <Subscriptions<T>>::insert(sender.clone(), creator.clone(), Subscription { tier, price, paid_until, cancelled: false });
+            <RenewalsAt<T>>::mutate(paid_until, |renewals| renewals.push((sender.clone(), creator.clone())));
+
+            Self::deposit_event(RawEvent::Subscribed(sender, creator, tier));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn cancel_subscription(_origin, creator: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let mut subscription = Self::subscriptions(sender.clone(), creator.clone()).ok_or("Not subscribed")?;
+            ensure!(!subscription.cancelled, "Subscription already cancelled");
+            subscription.cancelled = true;
+            <Subscriptions<T>>::insert(sender.clone(), creator.clone(), subscription);
+            Self::deposit_event(RawEvent::SubscriptionCancelled(sender, creator));
+            Ok(())
+        }
+
+        // unlocks the rewards that have vested so far
+        #[weight = 10_000]
+        pub fn vest(_origin) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            ensure!(<Vesting<T>>::contains_key(sender.clone()), "Nothing is vesting");
+            Self::_update_vesting_lock(&sender);
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn create_affiliate(_origin, url: Vec<u8>, token_id: TokenId, total_credit: T::TokenBalance, single_click_credit: T::TokenBalance, url_append: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            Self::_approve(token_id, sender.clone(), Self::treasury(), total_credit)?;
+            let mut affiliations = <Affiliations<T>>::get(url.clone());
+            affiliations.push(AffiliateProvider { token_id, total_credit, single_click_credit, payer: sender.clone(), url_append });
+            <Affiliations<T>>::insert(url, affiliations);
+            Ok(())
+        }
+
+        // pays out one click, signed by the treasury which `create_affiliate` approved as spender
+        #[weight = 10_000]
+        pub fn pay_affiliate(_origin, url: Vec<u8>, url_append: Vec<u8>, to: T::AccountId) -> DispatchResult {
+            let providers = Self::affiliations(url.clone());
+            for p in providers {
+                if p.url_append == url_append {
+                    return Ok(Self::transfer_from(_origin, p.token_id, p.payer, to, p.single_click_credit)?);
+                }
+            }
+            ensure!(false, "affiliation does not exist");
+            Ok(())
+        }
+    }
+}
+
+// implementation of mudule
+// utility and private functions
+// if marked public, accessible by other modules
+impl<T: Trait> Module<T> {
+    fn _create_token(
+        token_id: TokenId,
+        issuer: T::AccountId,
+        name: Vec<u8>,
+        ticker: Vec<u8>,
+        decimals: u8,
+        total_supply: T::TokenBalance,
+    ) -> DispatchResult {
+        // checking max size for name and ticker
+        // byte arrays (vecs) with no max size should be avoided
+        ensure!(name.len() <= 64, "token name cannot exceed 64 bytes");
+        ensure!(ticker.len() <= 32, "token ticker cannot exceed 32 bytes");
+
+        let token = Erc20Token {
+            name,
+            ticker,
+            total_supply,
+            decimals,
+            ..Default::default()
+        };
+        <TokenInfo<T>>::insert(token_id, token);
+        <TokenIssuer<T>>::insert(token_id, issuer.clone());
+        Self::_set_balance(token_id, &issuer, total_supply);
+        Self::_checkpoint_supply(token_id, Zero::zero());
+
+        Self::deposit_event(RawEvent::TokenCreated(token_id, issuer, total_supply));
+        Ok(())
+    }
+
+    // the ERC20 standard transfer function
+    // internal
+    fn _transfer(token_id: TokenId, from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        ensure!(!Self::paused(), "Transfers are paused");
+        ensure!(!Self::frozen(from.clone()) && !Self::frozen(to.clone()), "Account is frozen");
+        ensure!(<BalanceOf<T>>::contains_key(token_id, from.clone()), "Account does not own this token");
+        let sender_balance = Self::balance_of(token_id, from.clone());
+        ensure!(sender_balance >= value, "Not enough balance.");
+
+        let updated_from_balance =
+            sender_balance.checked_sub(&value).ok_or("overflow in calculating balance")?;
+        Self::_ensure_unlocked(token_id, &from, updated_from_balance)?;
+        let receiver_balance = Self::balance_of(token_id, to.clone());
+        let updated_to_balance =
+            receiver_balance.checked_add(&value).ok_or("overflow in calculating balance")?;
+
+        // reduce sender's balance
+        Self::_set_balance(token_id, &from, updated_from_balance);
+
+        // increase receiver's balance
+        Self::_set_balance(token_id, &to, updated_to_balance);
+
+        Self::deposit_event(RawEvent::Transfer(token_id, from, to, value));
+        Ok(())
+    }
+
+    // creates new social tokens for an account, increasing the total supply
+    fn _mint(to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        let mut token = Self::token_info(SOCIAL_TOKEN);
+        let previous = token.total_supply;
+        token.total_supply = token.total_supply.checked_add(&value).ok_or("overflow in calculating total supply")?;
+        Self::_credit(to, value)?;
+        <TokenInfo<T>>::insert(SOCIAL_TOKEN, token);
+        Self::_checkpoint_supply(SOCIAL_TOKEN, previous);
+        Ok(())
+    }
+
+    fn _burn(token_id: TokenId, from: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        ensure!(!Self::frozen(from.clone()), "Account is frozen");
+        ensure!(<BalanceOf<T>>::contains_key(token_id, from.clone()), "Account does not own this token");
+        let balance = Self::balance_of(token_id, from.clone());
+        ensure!(balance >= value, "Not enough balance.");
+
+        let updated_balance = balance.checked_sub(&value).ok_or("overflow in calculating balance")?;
+        Self::_ensure_unlocked(token_id, &from, updated_balance)?;
+        let mut token = Self::token_info(token_id);
+        let previous = token.total_supply;
+        token.total_supply = token.total_supply.checked_sub(&value).ok_or("overflow in calculating total supply")?;
+        Self::_set_balance(token_id, &from, updated_balance);
+        <TokenInfo<T>>::insert(token_id, token);
+        Self::_checkpoint_supply(token_id, previous);
+
+        Self::deposit_event(RawEvent::Burned(token_id, from, value));
+        Ok(())
+    }
+
+    // every write to `BalanceOf` goes through here so `balance_at` can look it up later
+    fn _set_balance(token_id: TokenId, who: &T::AccountId, value: T::TokenBalance) {
+        let previous = <BalanceOf<T>>::mutate(token_id, who, |balance| sp_std::mem::replace(balance, value));
+        <BalanceCheckpoints<T>>::mutate(token_id, who, |checkpoints| Self::_checkpoint(checkpoints, previous, value));
+    }
+
+    // records the current total supply, call after every change to it with the supply before it
+    fn _checkpoint_supply(token_id: TokenId, previous: T::TokenBalance) {
+        let total_supply = Self::token_info(token_id).total_supply;
+        <SupplyCheckpoints<T>>::mutate(token_id, |checkpoints| Self::_checkpoint(checkpoints, previous, total_supply));
+    }
+
+    // only the last value written within a block is kept, the first write also records `previous`
+    // at block zero so values from before checkpoints existed can still be looked up
+    fn _checkpoint(
+        checkpoints: &mut Vec<(T::BlockNumber, T::TokenBalance)>,
+        previous: T::TokenBalance,
+        value: T::TokenBalance,
+    ) {
+        let now = <system::Module<T>>::block_number();
+        if checkpoints.is_empty() {
+            checkpoints.push((Zero::zero(), previous));
+        }
+        match checkpoints.last_mut() {
+            Some((block, last)) if *block == now => *last = value,
+            _ => checkpoints.push((now, value)),
+        }
+        if checkpoints.len() > MAX_CHECKPOINTS {
+            checkpoints.remove(0);
+        }
+    }
+
+    // value at the end of `block`, `current` if nothing was written since checkpoints were added,
+    // `None` if `block` is older than the checkpoints kept
+    fn _value_at(
+        checkpoints: Vec<(T::BlockNumber, T::TokenBalance)>,
+        block: T::BlockNumber,
+        current: T::TokenBalance,
+    ) -> Option<T::TokenBalance> {
+        if checkpoints.is_empty() {
+            return Some(current);
+        }
+        match checkpoints.binary_search_by_key(&block, |(n, _)| *n) {
+            Ok(i) => Some(checkpoints[i].1),
+            Err(0) => None,
+            Err(i) => Some(checkpoints[i - 1].1),
+        }
+    }
+
+    // social token balance of `who` at the end of `block`, `None` if it's older than the kept checkpoints
+    pub fn balance_at(who: T::AccountId, block: T::BlockNumber) -> Option<T::TokenBalance> {
+        let current = Self::balance_of(SOCIAL_TOKEN, who.clone());
+        Self::_value_at(Self::balance_checkpoints(SOCIAL_TOKEN, who), block, current)
+    }
+
+    // social token total supply at the end of `block`, `None` if it's older than the kept checkpoints
+    pub fn total_supply_at(block: T::BlockNumber) -> Option<T::TokenBalance> {
+        let current = Self::token_info(SOCIAL_TOKEN).total_supply;
+        Self::_value_at(Self::supply_checkpoints(SOCIAL_TOKEN), block, current)
+    }
+
+    // holds the tokens of one airdrop, apart from the treasury and the other airdrops
+    fn _airdrop_account(airdrop_id: AirdropId) -> T::AccountId {
+        AIRDROP_MODULE_ID.into_sub_account(airdrop_id)
+    }
+
+    // administrative calls are open to root and the treasury set in `init`
+    fn _ensure_admin(origin: T::Origin) -> DispatchResult {
+        if ensure_root(origin.clone()).is_ok() {
+            return Ok(());
+        }
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::initialized() && sender == Self::treasury(), "Only root or the treasury can do this");
+        Ok(())
+    }
+
+    // the largest social token lock on an account
+    fn _locked(who: &T::AccountId) -> T::TokenBalance {
+        Self::locks(who).iter().map(|(_, amount)| *amount).max().unwrap_or_else(Zero::zero)
+    }
+
+    // only social tokens can be locked, other tokens are always free
+    fn _ensure_unlocked(token_id: TokenId, who: &T::AccountId, new_balance: T::TokenBalance) -> DispatchResult {
+        if token_id == SOCIAL_TOKEN {
+            ensure!(new_balance >= Self::_locked(who), "Balance is locked");
+        }
+        Ok(())
+    }
+
+    // adds social tokens to an account, without changing the total supply
+    fn _credit(to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        ensure!(!Self::frozen(to.clone()), "Account is frozen");
+        Self::_return_escrow(to, value)
+    }
+
+    // gives back social tokens the module held for an account, frozen or not, as they were its own
+    fn _return_escrow(to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        let receiver_balance = Self::balance_of(SOCIAL_TOKEN, to.clone());
+        let updated_to_balance =
+            receiver_balance.checked_add(&value).ok_or("overflow in calculating balance")?;
+        Self::_set_balance(SOCIAL_TOKEN, &to, updated_to_balance);
+        Ok(())
+    }
+
+    fn _upload(
+        owner: T::AccountId,
+        photo: Vec<u8>,
+        affiliate_url: Option<Vec<u8>>,
+        caption: Option<Vec<u8>>,
+        tags: Vec<Vec<u8>>,
+        private: bool,
+    ) -> DispatchResult {
+        ensure!(!<Photos<T>>::contains_key(photo.clone()), "This photo already uploaded");
+        // TODO: off chain verify this is actually exist in ipfs, and it's a photo.
+        Self::_ensure_valid_caption(&caption)?;
+        let tags = Self::_normalize_tags(tags)?;
+
+        let mut photo_info = PhotoInfo { owner: owner.clone(), affiliate_url, caption, tags, private, ..Default::default() };
+        photo_info.deposit = Self::_update_deposit(&owner, Default::default(), Self::_photo_bytes(&photo, &photo_info))?;
+        Self::_index_tags(&photo, &photo_info.tags);
+        <Photos<T>>::insert(photo.clone(), photo_info);
+        let mut account = Self::accounts(owner.clone());
+        account.photos.push(photo);
+        <Accounts<T>>::insert(owner, account);
+        Ok(())
+    }
+
+    // bounds the mentions, drops duplicates and checks every mentioned account has a profile
+    fn _validate_mentions(mentions: Vec<T::AccountId>) -> Result<Vec<T::AccountId>, DispatchError> {
+        ensure!(mentions.len() <= MAX_MENTIONS, "too many mentions");
+        let mut validated: Vec<T::AccountId> = Vec::new();
+        for who in mentions {
+            ensure!(<Accounts<T>>::contains_key(who.clone()), "Mentioned account doesn't exist");
+            if !validated.contains(&who) {
+                validated.push(who);
+            }
+        }
+        Ok(validated)
+    }
+
+    // emits a `Mentioned` event per account under a topic derived from that account,
+    // so a client can subscribe to its own notification stream
+    fn _notify_mentions(author: &T::AccountId, photo: &[u8], comment: Option<u32>, mentions: Vec<T::AccountId>) {
+        for who in mentions {
+            let topic = T::Hashing::hash_of(&who);
+            let event = <T as Trait>::Event::from(RawEvent::Mentioned(who, author.clone(), photo.to_vec(), comment));
+            <system::Module<T>>::deposit_event_indexed(&[topic], event.into());
+        }
+    }
+
+    fn _ensure_can_view(
+        photo: &[u8],
+        info: &PhotoInfo<T::AccountId, DepositBalanceOf<T>>,
+        who: &T::AccountId,
+    ) -> DispatchResult {
+        if info.private && &info.owner != who {
+            ensure!(<AccessKeys<T>>::contains_key(photo.to_vec(), who), "No access to this photo");
+        }
+        Ok(())
+    }
+
+    // whether `subscriber` has paid for the current period of a subscription to `creator`
+    pub fn is_subscribed(subscriber: T::AccountId, creator: T::AccountId) -> bool {
+        let now = <system::Module<T>>::block_number();
+        Self::subscriptions(subscriber, creator).map_or(false, |s| now < s.paid_until)
+    }
+
+    // renews the subscriptions due from `RenewalCursor` up to `now`, bounded per block
+    // nothing is touched while transfers are paused, the due renewals wait in `RenewalsAt`
+    fn _charge_renewals(now: T::BlockNumber) -> Weight {
+        if Self::paused() {
+            return T::DbWeight::get().reads(1);
+        }
+        let mut block = Self::renewal_cursor();
+        let mut blocks: u32 = 0;
+        let mut renewed: usize = 0;
+        while block <= now && blocks < MAX_RENEWAL_BLOCKS_PER_BLOCK && renewed < MAX_RENEWALS_PER_BLOCK {
+            let mut renewals = <RenewalsAt<T>>::take(block);
+            blocks += 1;
+            let left = MAX_RENEWALS_PER_BLOCK - renewed;
+            let rest = if renewals.len() > left { renewals.split_off(left) } else { Vec::new() };
+            renewed += renewals.len();
+            for (subscriber, creator) in renewals {
+                Self::_renew_subscription(subscriber, creator, now);
+            }
+            if !rest.is_empty() {
+                // the rest stays at this block, the cursor doesn't move past it
+                <RenewalsAt<T>>::insert(block, rest);
+                break;
+            }
+            block = block.saturating_add(One::one());
+        }
+        <RenewalCursor<T>>::put(block);
+        // the pause and the cursor, then every block of renewals gone through
+        T::DbWeight::get().reads_writes(2, 1)
+            .saturating_add(T::DbWeight::get().reads_writes(1, 2).saturating_mul(blocks as Weight))
+            .saturating_add(T::DbWeight::get().reads_writes(4, 4).saturating_mul(renewed as Weight))
+    }
+
+    // charges the next period from the allowance, or ends the subscription if it was
+    // cancelled, its tier is gone or the payment fails
+    fn _renew_subscription(subscriber: T::AccountId, creator: T::AccountId, now: T::BlockNumber) {
+        let mut subscription = match Self::subscriptions(subscriber.clone(), creator.clone()) {
+            Some(subscription) => subscription,
+            None => return,
+        };
+        if subscription.cancelled {
+            <Subscriptions<T>>::remove(subscriber, creator);
+            return;
+        }
+        let charged = <SubscriptionTiers<T>>::contains_key(creator.clone(), subscription.tier)
+            && Self::_spend_allowance(SOCIAL_TOKEN, subscriber.clone(), creator.clone(), creator.clone(), subscription.price).is_ok();
+        if !charged {
+            <Subscriptions<T>>::remove(subscriber.clone(), creator.clone());
+            Self::deposit_event(RawEvent::SubscriptionLapsed(subscriber, creator));
+            return;
+        }
+
+        subscription.paid_until = now.saturating_add(T::SubscriptionPeriod::get());
+        <RenewalsAt<T>>::mutate(subscription.paid_until, |renewals| renewals.push((subscriber.clone(), creator.clone())));
+        let price = subscription.price;
+        <Subscriptions<T>>::insert(subscriber.clone(), creator.clone(), subscription);
+        Self::deposit_event(RawEvent::SubscriptionRenewed(subscriber, creator, price));
+    }
+
+    // transfers `owner`'s tokens to `to` out of the allowance `owner` approved to `spender`
+    fn _spend_allowance(
+        token_id: TokenId,
+        owner: T::AccountId,
+        spender: T::AccountId,
+        to: T::AccountId,
+        value: T::TokenBalance,
+    ) -> DispatchResult {
+        let allowance = Self::allowance(token_id, (owner.clone(), spender.clone()));
+        ensure!(allowance >= value, "Not enough allowance.");
+        // using checked_sub (safe math) to avoid overflow
+        let updated_allowance = allowance.checked_sub(&value).ok_or("overflow in calculating allowance")?;
+        Self::_transfer(token_id, owner.clone(), to, value)?;
+        <Allowance<T>>::insert(token_id, (owner, spender), updated_allowance);
+        Ok(())
+    }
+
+    // removes social tokens from an account
+    fn _debit(from: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        ensure!(!Self::frozen(from.clone()), "Account is frozen");
+        let sender_balance = Self::balance_of(SOCIAL_TOKEN, from.clone());
+        ensure!(sender_balance >= value, "Not enough balance.");
+        let updated_from_balance =
+            sender_balance.checked_sub(&value).ok_or("overflow in calculating balance")?;
+        Self::_ensure_unlocked(SOCIAL_TOKEN, &from, updated_from_balance)?;
+        Self::_set_balance(SOCIAL_TOKEN, &from, updated_from_balance);
+        Ok(())
+    }
+
+    // draws `JurySize` distinct jurors with at least `stake` staked, none of them in `excluded`
+    // `RandomnessCollectiveFlip` is predictable by block authors, good enough for low stakes only
+    fn _draw_jury(
+        dispute_id: DisputeId,
+        excluded: &[T::AccountId],
+        stake: T::TokenBalance,
+    ) -> Result<Vec<T::AccountId>, DispatchError> {
+        let mut candidates: Vec<T::AccountId> = Self::juror_pool()
+            .into_iter()
+            .filter(|j| !excluded.contains(j) && Self::juror_stakes(j) >= stake)
+            .collect();
+        let jury_size = T::JurySize::get();
+        ensure!(candidates.len() >= jury_size as usize, "Not enough jurors");
+
+        let seed = T::Randomness::random(&(b"jury", dispute_id).encode());
+        let mut jurors = Vec::new();
+        for i in 0..jury_size {
+            let random = T::Hashing::hash_of(&(seed, i));
+            let index = u32::decode(&mut random.as_ref()).unwrap_or_default() as usize % candidates.len();
+            jurors.push(candidates.swap_remove(index));
+        }
+        Ok(jurors)
+    }
+
+    // page through the photos carrying `tag`, used by the runtime api
+    pub fn photos_by_tag_page(tag: Vec<u8>, start: u32, limit: u32) -> Vec<Vec<u8>> {
+        <PhotosByTag<T>>::iter_prefix(Self::_tag_hash(&tag.to_ascii_lowercase()))
+            .map(|(photo, _)| photo)
+            .filter(|photo| !Self::photos(photo).hidden)
+            .skip(start as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // page through the followers of `account`, used by the runtime api
+    pub fn followers_page(account: T::AccountId, start: u32, limit: u32) -> Vec<T::AccountId> {
+        <Followers<T>>::iter_prefix(account)
+            .skip(start as usize)
+            .take(limit as usize)
+            .map(|(follower, _)| follower)
+            .collect()
+    }
+
+    // handles are 3 to 32 characters of `a-z`, `0-9` and `_`
+    fn _ensure_valid_handle(handle: &[u8]) -> DispatchResult {
+        ensure!(handle.len() >= MIN_HANDLE_LEN, "handle is too short");
+        ensure!(handle.len() <= MAX_HANDLE_LEN, "handle is too long");
+        ensure!(
+            handle.iter().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'_'),
+            "handle contains invalid characters"
+        );
+        Ok(())
+    }
+
+    // reserves or returns the difference between the deposit held for `bytes` of content and `old`
+    fn _update_deposit(
+        who: &T::AccountId,
+        old: DepositBalanceOf<T>,
+        bytes: usize,
+    ) -> Result<DepositBalanceOf<T>, DispatchError> {
+        let new = T::DepositPerByte::get().saturating_mul((bytes as u32).into());
+        if new > old {
+            T::Currency::reserve(who, new - old)?;
+        } else if new < old {
+            T::Currency::unreserve(who, old - new);
+        }
+        Ok(new)
+    }
+
+    // bytes a photo keeps in storage on behalf of its owner, comments are paid by their authors
+    fn _photo_bytes(photo: &[u8], info: &PhotoInfo<T::AccountId, DepositBalanceOf<T>>) -> usize {
+        photo.len()
+            + info.affiliate_url.as_ref().map_or(0, |u| u.len())
+            + info.caption.as_ref().map_or(0, |c| c.len())
+            + info.tags.iter().map(|t| t.len()).sum::<usize>()
+    }
+
+    // the top `limit` trending photos with their scores decayed to the current block, used by the runtime api
+    pub fn trending_page(limit: u32) -> Vec<(Vec<u8>, u64)> {
+        let decay = Self::_decay_since(Self::trending_updated_at());
+        Self::trending()
+            .into_iter()
+            .filter(|(photo, _)| !Self::photos(photo).hidden)
+            .take(limit as usize)
+            .map(|(photo, score)| (photo, decay * score))
+            .collect()
+    }
+
+    // reports waiting for a moderator, either open or appealed, used by the runtime api
+    pub fn pending_reports(start: u32, limit: u32) -> Vec<(ReportId, Report<T::AccountId, T::BlockNumber>)> {
+        <PendingReports>::iter()
+            .skip(start as usize)
+            .take(limit as usize)
+            .filter_map(|(report_id, _)| Self::reports(report_id).map(|report| (report_id, report)))
+            .collect()
+    }
+
+    fn _report(reporter: T::AccountId, content: ContentRef, reason: ReportReason) -> DispatchResult {
+        let report_id = Self::next_report_id();
+        let next_id = report_id.checked_add(1).ok_or("overflow in calculating report id")?;
+        <NextReportId>::put(next_id);
+        <Reports<T>>::insert(report_id, Report {
+            reporter: reporter.clone(),
+            content,
+            reason,
+            created: <system::Module<T>>::block_number(),
+            status: ReportStatus::Open,
+            appeal: None,
+        });
+        <PendingReports>::insert(report_id, true);
+        Self::deposit_event(RawEvent::Reported(report_id, reporter));
+        Ok(())
+    }
+
+    fn _content_exists(content: &ContentRef) -> bool {
+        match content {
+            ContentRef::Photo(photo) => <Photos<T>>::contains_key(photo),
+            ContentRef::Comment(photo, index) => <Comments<T>>::contains_key(photo, index),
+        }
+    }
+
+    fn _content_owner(content: &ContentRef) -> Result<T::AccountId, DispatchError> {
+        match content {
+            ContentRef::Photo(photo) => {
+                ensure!(<Photos<T>>::contains_key(photo), "Photo doesn't exist");
+                Ok(Self::photos(photo).owner)
+            }
+            ContentRef::Comment(photo, index) => {
+                let comment = Self::comments(photo, index).ok_or("Comment doesn't exist")?;
+                Ok(comment.author)
+            }
+        }
+    }
+
+    fn _set_hidden(content: &ContentRef, hidden: bool) -> DispatchResult {
+        match content {
+            ContentRef::Photo(photo) => {
+                ensure!(<Photos<T>>::contains_key(photo), "Photo doesn't exist");
+                <Photos<T>>::mutate(photo, |info| info.hidden = hidden);
+            }
+            ContentRef::Comment(photo, index) => {
+                ensure!(<Comments<T>>::contains_key(photo, index), "Comment doesn't exist");
+                <Comments<T>>::mutate(photo, index, |comment| {
+                    if let Some(comment) = comment {
+                        comment.hidden = hidden;
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // share of a score left after decaying from `since` to the current block
+    fn _decay_since(since: T::BlockNumber) -> Perbill {
+        let now = <system::Module<T>>::block_number();
+        let elapsed = now.saturating_sub(since).saturated_into::<u32>();
+        T::TrendingDecay::get().saturating_pow(elapsed as usize)
+    }
+
+    // adds `points` to the photo's decayed trending score and updates the top list
+    fn _bump_trending(photo: &[u8], points: u64) {
+        // private photos stay out of public listings
+        if Self::photos(photo.to_vec()).private {
+            return;
+        }
+        let now = <system::Module<T>>::block_number();
+        let (score, since) = Self::trending_scores(photo.to_vec());
+        let score = (Self::_decay_since(since) * score).saturating_add(points);
+        <TrendingScores<T>>::insert(photo.to_vec(), (score, now));
+
+        // every entry decays at the same rate, so bringing them all to `now` keeps the order comparable
+        let decay = Self::_decay_since(Self::trending_updated_at());
+        let mut trending: Vec<(Vec<u8>, u64)> = Self::trending()
+            .into_iter()
+            .filter(|(p, _)| &p[..] != photo)
+            .map(|(p, s)| (p, decay * s))
+            .collect();
+        trending.push((photo.to_vec(), score));
+        trending.sort_by(|a, b| b.1.cmp(&a.1));
+        trending.truncate(T::TrendingSize::get() as usize);
+        <Trending>::put(trending);
+        <TrendingUpdatedAt<T>>::put(now);
+    }
+
+    fn _tag_hash(tag: &[u8]) -> T::Hash {
+        T::Hashing::hash(tag)
+    }
+
+    fn _ensure_valid_caption(caption: &Option<Vec<u8>>) -> DispatchResult {
+        if let Some(caption) = caption {
+            ensure!(caption.len() <= MAX_CAPTION_LEN, "caption is too long");
+        }
+        Ok(())
+    }
+
+    // lowercases the tags and drops duplicates, so `#Sunset` and `#sunset` share an index entry
+    fn _normalize_tags(tags: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, &'static str> {
+        ensure!(tags.len() <= MAX_TAGS, "too many tags");
+        let mut normalized: Vec<Vec<u8>> = Vec::new();
+        for tag in tags {
+            ensure!(!tag.is_empty(), "tag cannot be empty");
+            ensure!(tag.len() <= MAX_TAG_LEN, "tag is too long");
+            let tag = tag.to_ascii_lowercase();
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        Ok(normalized)
+    }
+
+    fn _index_tags(photo: &[u8], tags: &[Vec<u8>]) {
+        for tag in tags {
+            <PhotosByTag<T>>::insert(Self::_tag_hash(tag), photo.to_vec(), ());
+        }
+    }
+
+    fn _unindex_tags(photo: &[u8], tags: &[Vec<u8>]) {
+        for tag in tags {
+            <PhotosByTag<T>>::remove(Self::_tag_hash(tag), photo.to_vec());
+        }
+    }
+
+    // applies `f` to one of the owner's albums and emits `AlbumUpdated`
+    fn _mutate_album(
+        owner: T::AccountId,
+        album_id: AlbumId,
+        f: impl FnOnce(&mut Album) -> DispatchResult,
+    ) -> DispatchResult {
+        let mut album = Self::albums(owner.clone(), album_id).ok_or("Album doesn't exist")?;
+        f(&mut album)?;
+        <Albums<T>>::insert(owner.clone(), album_id, album);
+        Self::deposit_event(RawEvent::AlbumUpdated(owner, album_id));
+        Ok(())
+    }
+
+    // whether the account created its profile at least `MinAccountAge` blocks ago
+    fn _old_enough(who: &T::AccountId) -> bool {
+        let now = <system::Module<T>>::block_number();
+        Self::joined_at(who).map_or(false, |joined| now.saturating_sub(joined) >= T::MinAccountAge::get())
+    }
+
+    // shares the reward pool between creators once an epoch is over, a block pays at most
+    // `MAX_EPOCH_PAYOUTS` photos of the oldest unpaid epoch and the rest follow in the next blocks
+    // every photo owner gets a share of `RewardPool` proportional to the unique likes the photo
+    // received in the epoch, scores of deleted photos are forfeited
+    fn _pay_epoch_rewards() -> Weight {
+        let epoch = Self::payout_epoch();
+        if epoch >= Self::_current_epoch() {
+            return T::DbWeight::get().reads(1);
+        }
+        let total = Self::epoch_total_score(epoch);
+        let pool = T::RewardPool::get();
+        let scores: Vec<(Vec<u8>, u32)> = <EpochScores<T>>::iter_prefix(epoch).take(MAX_EPOCH_PAYOUTS).collect();
+        let paid = scores.len();
+        for (photo, score) in scores {
+            <EpochScores<T>>::remove(epoch, photo.clone());
+            if total == 0 || !<Photos<T>>::contains_key(photo.clone()) {
+                continue;
+            }
+            let owner = Self::photos(photo).owner;
+            let share = Perbill::from_rational_approximation(score, total) * pool;
+            // an overflow only affects this owner's payout
+            let _ = Self::_reward(owner, share);
+        }
+        if paid < MAX_EPOCH_PAYOUTS {
+            <EpochTotalScore<T>>::remove(epoch);
+            <PayoutEpoch<T>>::put(epoch.saturating_add(One::one()));
+        }
+        // a payout reads the photo and the owner's reward state, then mints and vests
+        T::DbWeight::get().reads_writes(2, 2)
+            .saturating_add(T::DbWeight::get().reads_writes(10, 8).saturating_mul(paid as Weight))
+    }
+
+    fn _current_epoch() -> T::BlockNumber {
+        let epoch_length = T::RewardEpochLength::get();
+        if epoch_length.is_zero() {
+            Zero::zero()
+        } else {
+            <system::Module<T>>::block_number() / epoch_length
+        }
+    }
+
+    fn _current_era() -> T::BlockNumber {
+        let era_length = T::RewardEraLength::get();
+        if era_length.is_zero() {
+            Zero::zero()
+        } else {
+            <system::Module<T>>::block_number() / era_length
+        }
+    }
+
+    // feeless actions `who` has left in the current era
+    fn _free_actions_left(who: &T::AccountId) -> u32 {
+        let (era, used) = Self::free_actions_used(who);
+        let used = if era == Self::_current_era() { used } else { 0 };
+        T::FreeActionsPerEra::get().saturating_sub(used)
+    }
+
+    fn _use_free_action(who: &T::AccountId) {
+        let era = Self::_current_era();
+        <FreeActionsUsed<T>>::mutate(who, |(last_era, used)| {
+            if *last_era != era {
+                *last_era = era;
+                *used = 0;
+            }
+            *used = used.saturating_add(1);
+        });
+    }
+
+    // locks `value` more tokens in a schedule of their own starting now, earlier rewards keep vesting
+    // as they did; with `MAX_VESTING_SCHEDULES` running, the newest one takes the tokens instead and
+    // they vest by its end
+    fn _add_vesting(who: &T::AccountId, value: T::TokenBalance) {
+        let period = T::VestingPeriod::get();
+        if value.is_zero() || period.is_zero() {
+            return;
+        }
+        let now = <system::Module<T>>::block_number();
+        let mut schedules = Self::vesting(who);
+        schedules.retain(|schedule| !schedule.locked_at(now).is_zero());
+        if schedules.len() < MAX_VESTING_SCHEDULES {
+            schedules.push(VestingSchedule { locked: value, start: now, period });
+        } else if let Some(newest) = schedules.last_mut() {
+            // what the newest schedule still locks vests again over a whole period with `value`
+            newest.locked = newest.locked_at(now).saturating_add(value);
+            newest.start = now;
+            newest.period = period;
+        }
+        <Vesting<T>>::insert(who, schedules);
+        Self::_update_vesting_lock(who);
+    }
+
+    // shrinks the vesting lock to what is still locked, dropping schedules once fully vested
+    fn _update_vesting_lock(who: &T::AccountId) {
+        let now = <system::Module<T>>::block_number();
+        let mut schedules = Self::vesting(who);
+        schedules.retain(|schedule| !schedule.locked_at(now).is_zero());
+        let locked = schedules.iter()
+            .fold(T::TokenBalance::zero(), |locked, schedule| locked.saturating_add(schedule.locked_at(now)));
+        if schedules.is_empty() {
+            <Vesting<T>>::remove(who);
+            <Self as LockableCurrency<_>>::remove_lock(VESTING_ID, who);
+        } else {
+            <Vesting<T>>::insert(who, schedules);
+            <Self as LockableCurrency<_>>::set_lock(VESTING_ID, who, locked, WithdrawReasons::all());
+        }
+        Self::deposit_event(RawEvent::Vested(who.clone(), locked));
+    }
+
+    // mints a reward, or emits `RewardWithheld` when the account is too new or its era cap is used up
+    fn _reward(who: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        let era = Self::_current_era();
+        let (last_era, earned) = Self::rewarded_in_era(who.clone());
+        let earned = if last_era == era { earned } else { Default::default() };
+        let updated_earned = earned.checked_add(&value).ok_or("overflow in calculating reward")?;
+
+        let withheld = if Self::frozen(who.clone()) {
+            Some(WithheldReason::AccountFrozen)
+        } else if !Self::_old_enough(&who) {
+            Some(WithheldReason::AccountTooNew)
+        } else if updated_earned > T::MaxRewardPerEra::get() {
+            Some(WithheldReason::EraCapReached)
+        } else {
+            None
+        };
+
+        if let Some(reason) = withheld {
+            Self::deposit_event(RawEvent::RewardWithheld(who, value, reason));
+            return Ok(());
+        }
+        <RewardedInEra<T>>::insert(who.clone(), (era, updated_earned));
+        Self::_mint(who.clone(), value)?;
+        Self::_add_vesting(&who, T::VestedRewardShare::get() * value);
+        Self::deposit_event(RawEvent::Rewarded(who, value));
         Ok(())
     }
 
-    fn _approve(sender: T::AccountId, spender: T::AccountId, value: T::TokenBalance) -> DispatchResult {
-        ensure!(<BalanceOf<T>>::contains_key(sender.clone()), "Account does not own this token");
+    fn _approve(token_id: TokenId, sender: T::AccountId, spender: T::AccountId, value: T::TokenBalance) -> DispatchResult {
+        ensure!(<BalanceOf<T>>::contains_key(token_id, sender.clone()), "Account does not own this token");
 
-        let allowance = Self::allowance((sender.clone(), spender.clone()));
+        let allowance = Self::allowance(token_id, (sender.clone(), spender.clone()));
         let updated_allowance = allowance.checked_add(&value).ok_or("overflow in calculating allowance")?;
-        <Allowance<T>>::insert((sender.clone(), spender.clone()), updated_allowance);
+        <Allowance<T>>::insert(token_id, (sender.clone(), spender.clone()), updated_allowance);
 
-        Self::deposit_event(RawEvent::Approval(sender.clone(), spender.clone(), value));
+        Self::deposit_event(RawEvent::Approval(token_id, sender.clone(), spender.clone(), value));
 
         Ok(())
     }
@@ -0,0 +1,191 @@
+// Moves the storage of a chain from the first release (`Releases::V1`) to the current layout.
+// The `Old*` structs below are the first release's encodings, values whose storage item
+// changed kind are read through their raw keys. Run once from `on_runtime_upgrade`.
+
+use crate::{
+    AccountProfile, Accounts, AffiliateProvider, Affiliations, Allowance, BalanceOf, Comment,
+    CommentCount, Comments, Erc20Token, JoinedAt, NextCommentIndex, PhotoInfo, Photos,
+    RenewalCursor, SignupBonusPaid, TokenInfo, Trait, SOCIAL_TOKEN,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+    storage::migration::{take_storage_value, StorageIterator},
+    traits::Get,
+    weights::Weight,
+    IterableStorageMap,
+    StorageDoubleMap, StorageMap, StorageValue,
+};
+use sp_runtime::traits::{Saturating, Zero};
+use sp_std::{cell::Cell, vec::Vec};
+
+const MODULE: &[u8] = b"Erc20";
+
+// `Erc20Token` before `decimals`, `icon` and `description` were added
+#[derive(Encode, Decode)]
+pub struct OldErc20Token<U> {
+    pub name: Vec<u8>,
+    pub ticker: Vec<u8>,
+    pub total_supply: U,
+}
+
+// `AffiliateProvider` before campaigns could pay out in any token
+#[derive(Encode, Decode)]
+pub struct OldAffiliateProvider<TokenBalance, AccountId> {
+    pub single_click_credit: TokenBalance,
+    pub total_credit: TokenBalance,
+    pub payer: AccountId,
+    pub url_append: Vec<u8>,
+}
+
+// `AccountProfile` before bios, links, follows and deposits
+#[derive(Encode, Decode)]
+pub struct OldAccountProfile {
+    pub name: Vec<u8>,
+    pub avatar: Vec<u8>,
+    pub photos: Vec<Vec<u8>>,
+}
+
+// `PhotoInfo` before captions, tags, deposits, moderation and private photos,
+// comments were bare text kept in the photo
+#[derive(Encode, Decode)]
+pub struct OldPhotoInfo<AccountId> {
+    pub owner: AccountId,
+    pub affiliate_url: Option<Vec<u8>>,
+    pub likes: Vec<AccountId>,
+    pub variants: Vec<Vec<u8>>,
+    pub comments: Vec<Vec<u8>>,
+}
+
+pub fn migrate<T: Trait>() -> Weight {
+    migrate_token_info::<T>()
+        .saturating_add(migrate_balances::<T>())
+        .saturating_add(migrate_affiliations::<T>())
+        .saturating_add(migrate_accounts::<T>())
+        .saturating_add(migrate_photos::<T>())
+        .saturating_add(start_renewals::<T>())
+}
+
+// the key of a `blake2_128_concat` map entry, stored after its 16 byte hash
+fn blake2_128_concat_key<K: Decode>(hashed: &[u8]) -> Option<K> {
+    hashed.get(16..).and_then(|mut raw| K::decode(&mut raw).ok())
+}
+
+// `TokenInfo` was a single value, it becomes the `SOCIAL_TOKEN` entry of the map
+fn migrate_token_info<T: Trait>() -> Weight {
+    let old = match take_storage_value::<OldErc20Token<T::TokenBalance>>(MODULE, b"TokenInfo", &[]) {
+        Some(old) => old,
+        None => return T::DbWeight::get().reads(1),
+    };
+    let token = Erc20Token {
+        name: old.name,
+        ticker: old.ticker,
+        total_supply: old.total_supply,
+        decimals: T::SocialTokenDecimals::get(),
+        icon: Vec::new(),
+        description: Vec::new(),
+    };
+    <TokenInfo<T>>::insert(SOCIAL_TOKEN, token);
+    T::DbWeight::get().reads_writes(1, 2)
+}
+
+// `BalanceOf` and `Allowance` were maps of the one token, their entries move under `SOCIAL_TOKEN`
+// the first release minted rewards without adding them to the total supply, which becomes the
+// sum of the balances
+fn migrate_balances<T: Trait>() -> Weight {
+    // drained up front, the double maps share the prefix of the old maps
+    let balances: Vec<(Vec<u8>, T::TokenBalance)> =
+        StorageIterator::new(MODULE, b"BalanceOf").drain().collect();
+    let allowances: Vec<(Vec<u8>, T::TokenBalance)> =
+        StorageIterator::new(MODULE, b"Allowance").drain().collect();
+    let migrated = (balances.len() + allowances.len()) as Weight;
+
+    let mut total_supply = T::TokenBalance::zero();
+    for (key, balance) in balances {
+        if let Some(who) = blake2_128_concat_key::<T::AccountId>(&key) {
+            <BalanceOf<T>>::insert(SOCIAL_TOKEN, who, balance);
+            total_supply = total_supply.saturating_add(balance);
+        }
+    }
+    if <TokenInfo<T>>::contains_key(SOCIAL_TOKEN) {
+        <TokenInfo<T>>::mutate(SOCIAL_TOKEN, |token| token.total_supply = total_supply);
+    }
+    for (key, allowance) in allowances {
+        if let Some(pair) = blake2_128_concat_key::<(T::AccountId, T::AccountId)>(&key) {
+            <Allowance<T>>::insert(SOCIAL_TOKEN, pair, allowance);
+        }
+    }
+    T::DbWeight::get().reads_writes(migrated.saturating_add(1), migrated.saturating_mul(2).saturating_add(1))
+}
+
+// existing campaigns pay out in the social token
+fn migrate_affiliations<T: Trait>() -> Weight {
+    let affiliations: Vec<(Vec<u8>, Vec<OldAffiliateProvider<T::TokenBalance, T::AccountId>>)> =
+        StorageIterator::new(MODULE, b"Affiliations").drain().collect();
+    let migrated = affiliations.len() as Weight;
+
+    for (key, providers) in affiliations {
+        if let Some(url) = blake2_128_concat_key::<Vec<u8>>(&key) {
+            let providers: Vec<_> = providers
+                .into_iter()
+                .map(|old| AffiliateProvider {
+                    token_id: SOCIAL_TOKEN,
+                    single_click_credit: old.single_click_credit,
+                    total_credit: old.total_credit,
+                    payer: old.payer,
+                    url_append: old.url_append,
+                })
+                .collect();
+            <Affiliations<T>>::insert(url, providers);
+        }
+    }
+    T::DbWeight::get().reads_writes(migrated, migrated.saturating_mul(2))
+}
+
+// existing profiles count as old enough for rewards and already got their signup bonus
+fn migrate_accounts<T: Trait>() -> Weight {
+    let migrated = Cell::new(0 as Weight);
+    <Accounts<T>>::translate::<OldAccountProfile, _>(|who, old| {
+        migrated.set(migrated.get() + 1);
+        <JoinedAt<T>>::insert(who.clone(), T::BlockNumber::zero());
+        <SignupBonusPaid<T>>::insert(who, true);
+        Some(AccountProfile {
+            name: old.name,
+            avatar: old.avatar,
+            photos: old.photos,
+            ..Default::default()
+        })
+    });
+    T::DbWeight::get().reads_writes(migrated.get(), migrated.get().saturating_mul(3))
+}
+
+// comments move into `Comments`, the first release didn't record their authors so they are
+// attributed to the photo owner; the photos and comments hold no deposit
+fn migrate_photos<T: Trait>() -> Weight {
+    let migrated = Cell::new(0 as Weight);
+    <Photos<T>>::translate::<OldPhotoInfo<T::AccountId>, _>(|photo, old| {
+        let count = old.comments.len() as u32;
+        migrated.set(migrated.get() + 3 + count as Weight);
+        let owner = old.owner;
+        for (index, text) in old.comments.into_iter().enumerate() {
+            let comment = Comment { author: owner.clone(), text, ..Default::default() };
+            <Comments<T>>::insert(photo.clone(), index as u32, comment);
+        }
+        <NextCommentIndex>::insert(photo.clone(), count);
+        <CommentCount>::insert(photo, count);
+        Some(PhotoInfo {
+            owner,
+            affiliate_url: old.affiliate_url,
+            likes: old.likes,
+            variants: old.variants,
+            ..Default::default()
+        })
+    });
+    T::DbWeight::get().reads_writes(migrated.get(), migrated.get())
+}
+
+// subscriptions came after the first release, their renewals start at the upgrade instead of
+// going through every block since genesis
+fn start_renewals<T: Trait>() -> Weight {
+    <RenewalCursor<T>>::put(<frame_system::Module<T>>::block_number());
+    T::DbWeight::get().reads_writes(1, 1)
+}
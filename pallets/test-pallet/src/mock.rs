@@ -1,7 +1,10 @@
 // Creating mock runtime here
 
 use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{
+    impl_outer_dispatch, impl_outer_origin, parameter_types,
+    weights::{IdentityFee, Weight},
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -14,6 +17,14 @@ impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+// the signed extensions in `fees` look at the outer call
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        test_pallet::TemplateModule,
+    }
+}
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -24,11 +35,33 @@ parameter_types! {
     pub const MaximumBlockWeight: Weight = 1024;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const ExistentialDeposit: u64 = 1;
+    pub const TransactionByteFee: u64 = 1;
+    pub const HandleDeposit: u64 = 10;
+    pub const DepositPerByte: u64 = 1;
+    pub const CommentDeposit: u128 = 1;
+    pub const RewardEraLength: u64 = 100;
+    pub const MaxRewardPerEra: u128 = 500;
+    pub const MinAccountAge: u64 = 10;
+    pub const RewardEpochLength: u64 = 10;
+    pub const RewardPool: u128 = 100;
+    pub const TrendingDecay: Perbill = Perbill::from_percent(99);
+    pub const TrendingSize: u32 = 10;
+    pub const JurySize: u32 = 3;
+    pub const JuryStake: u128 = 10;
+    pub const CommitPeriod: u64 = 5;
+    pub const RevealPeriod: u64 = 5;
+    pub const SubscriptionPeriod: u64 = 30;
+    pub const FreeActionsPerEra: u32 = 5;
+    pub const VestedRewardShare: Perbill = Perbill::from_percent(50);
+    pub const VestingPeriod: u64 = 10;
+    pub const SocialTokenDecimals: u8 = 0;
+    pub SocialTokenTicker: Vec<u8> = b"SOC".to_vec();
 }
 impl system::Trait for Test {
     type BaseCallFilter = ();
     type Origin = Origin;
-    type Call = ();
+    type Call = Call;
     type Index = u64;
     type BlockNumber = u64;
     type Hash = H256;
@@ -47,14 +80,52 @@ impl system::Trait for Test {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type ModuleToIndex = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
 }
+impl pallet_balances::Trait for Test {
+    type Balance = u64;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+}
+impl pallet_transaction_payment::Trait for Test {
+    type Currency = Balances;
+    type OnTransactionPayment = ();
+    type TransactionByteFee = TransactionByteFee;
+    type WeightToFee = IdentityFee<u64>;
+    type FeeMultiplierUpdate = ();
+}
 impl Trait for Test {
     type Event = ();
     type TokenBalance = u128;
+    type Currency = Balances;
+    type HandleDeposit = HandleDeposit;
+    type DepositPerByte = DepositPerByte;
+    type CommentDeposit = CommentDeposit;
+    type RewardEraLength = RewardEraLength;
+    type MaxRewardPerEra = MaxRewardPerEra;
+    type MinAccountAge = MinAccountAge;
+    type RewardEpochLength = RewardEpochLength;
+    type RewardPool = RewardPool;
+    type TrendingDecay = TrendingDecay;
+    type TrendingSize = TrendingSize;
+    type Randomness = ();
+    type JurySize = JurySize;
+    type JuryStake = JuryStake;
+    type CommitPeriod = CommitPeriod;
+    type RevealPeriod = RevealPeriod;
+    type SubscriptionPeriod = SubscriptionPeriod;
+    type FreeActionsPerEra = FreeActionsPerEra;
+    type VestedRewardShare = VestedRewardShare;
+    type VestingPeriod = VestingPeriod;
+    type SocialTokenDecimals = SocialTokenDecimals;
+    type SocialTokenTicker = SocialTokenTicker;
 }
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 pub type TemplateModule = Module<Test>;
 
 // This function basically just builds a genesis storage key/value store according to
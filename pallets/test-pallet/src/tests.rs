@@ -1,7 +1,27 @@
 // Tests to be written here
 
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	migration::{OldAccountProfile, OldErc20Token, OldPhotoInfo},
+	mock::*,
+	ChargeFeeInToken, Error, LimitFreeActions, ReportReason, ReportStatus, MAX_CHECKPOINTS,
+	MAX_COMMENTS_PER_PHOTO, SOCIAL_TOKEN, VESTING_ID,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::migration::put_storage_value,
+	traits::{
+		Currency, ExistenceRequirement, Get, Imbalance, OnInitialize, OnRuntimeUpgrade,
+		ReservableCurrency, WithdrawReasons,
+	},
+	weights::{DispatchClass, DispatchInfo, Pays},
+	Blake2_128Concat, StorageHasher,
+};
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidity},
+	FixedPointNumber, FixedU128,
+};
 
 // #[test]
 // fn it_works_for_default_value() {
@@ -24,3 +44,598 @@ use frame_support::{assert_noop, assert_ok};
 // 		);
 // 	});
 // }
+
+#[test]
+fn runtime_upgrade_moves_the_first_release_token_into_the_map() {
+	new_test_ext().execute_with(|| {
+		let old = OldErc20Token { name: b"Shine".to_vec(), ticker: b"SOC".to_vec(), total_supply: 1_000u128 };
+		put_storage_value(b"Erc20", b"TokenInfo", &[], old);
+
+		TemplateModule::on_runtime_upgrade();
+
+		let token = TemplateModule::token_info(SOCIAL_TOKEN);
+		assert_eq!(token.name, b"Shine".to_vec());
+		assert_eq!(token.ticker, b"SOC".to_vec());
+		assert_eq!(token.total_supply, 1_000);
+		assert_eq!(token.decimals, SocialTokenDecimals::get());
+		assert!(token.icon.is_empty() && token.description.is_empty());
+
+		// the version is bumped, a second upgrade leaves the token alone
+		TemplateModule::on_runtime_upgrade();
+		assert_eq!(TemplateModule::token_info(SOCIAL_TOKEN), token);
+	});
+}
+
+#[test]
+fn runtime_upgrade_moves_the_first_release_balances_under_the_social_token() {
+	new_test_ext().execute_with(|| {
+		put_storage_value(b"Erc20", b"BalanceOf", &Blake2_128Concat::hash(&1u64.encode()), 700u128);
+		put_storage_value(b"Erc20", b"Allowance", &Blake2_128Concat::hash(&(1u64, 2u64).encode()), 50u128);
+
+		TemplateModule::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 700);
+		assert_eq!(TemplateModule::allowance(SOCIAL_TOKEN, (1, 2)), 50);
+	});
+}
+
+#[test]
+fn runtime_upgrade_sets_the_total_supply_to_the_migrated_balances() {
+	new_test_ext().execute_with(|| {
+		// rewards of the first release were minted without touching the recorded supply
+		let old = OldErc20Token { name: b"Shine".to_vec(), ticker: b"SOC".to_vec(), total_supply: 1_000u128 };
+		put_storage_value(b"Erc20", b"TokenInfo", &[], old);
+		put_storage_value(b"Erc20", b"BalanceOf", &Blake2_128Concat::hash(&100u64.encode()), 1_000u128);
+		put_storage_value(b"Erc20", b"BalanceOf", &Blake2_128Concat::hash(&1u64.encode()), 300u128);
+
+		TemplateModule::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::token_info(SOCIAL_TOKEN).total_supply, 1_300);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_300);
+		assert_ok!(TemplateModule::burn(Origin::signed(1), SOCIAL_TOKEN, 300));
+		assert_ok!(TemplateModule::burn(Origin::signed(100), SOCIAL_TOKEN, 1_000));
+		assert_eq!(TemplateModule::token_info(SOCIAL_TOKEN).total_supply, 0);
+	});
+}
+
+#[test]
+fn runtime_upgrade_extends_the_first_release_profiles_and_photos() {
+	new_test_ext().execute_with(|| {
+		let profile = OldAccountProfile { name: b"alice".to_vec(), avatar: Vec::new(), photos: vec![b"cid".to_vec()] };
+		put_storage_value(b"Erc20", b"Accounts", &Blake2_128Concat::hash(&1u64.encode()), profile);
+		let photo = OldPhotoInfo {
+			owner: 1u64,
+			affiliate_url: None,
+			likes: vec![2u64],
+			variants: Vec::new(),
+			comments: vec![b"nice".to_vec()],
+		};
+		put_storage_value(b"Erc20", b"Photos", &Blake2_128Concat::hash(&b"cid".to_vec().encode()), photo);
+
+		TemplateModule::on_runtime_upgrade();
+
+		let profile = TemplateModule::accounts(1);
+		assert_eq!(profile.name, b"alice".to_vec());
+		assert_eq!(profile.photos, vec![b"cid".to_vec()]);
+		assert_eq!(TemplateModule::joined_at(1), Some(0));
+
+		let photo = TemplateModule::photos(b"cid".to_vec());
+		assert_eq!(photo.owner, 1);
+		assert_eq!(photo.likes, vec![2]);
+		assert_eq!(TemplateModule::comment_count(b"cid".to_vec()), 1);
+		assert_eq!(TemplateModule::comments(b"cid".to_vec(), 0).map(|c| c.text), Some(b"nice".to_vec()));
+	});
+}
+
+// the treasury (100) holds the whole social token supply
+fn init_social_token() {
+	assert_ok!(TemplateModule::init(Origin::signed(100), b"Shine".to_vec(), b"SOC".to_vec(), 1_000));
+}
+
+#[test]
+fn resolve_dispute_pays_the_remainder_of_the_pot_to_a_winner() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		for who in 1..=4u64 {
+			assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, who, 100));
+		}
+		for juror in 1..=3u64 {
+			assert_ok!(TemplateModule::stake_juror(Origin::signed(juror), 10));
+		}
+		let _ = Balances::deposit_creating(&5, 1_000);
+		assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"cid".to_vec(), None, None, Vec::new(), Vec::new()));
+
+		// 4 asks to hide the photo, every juror votes to keep it
+		assert_ok!(TemplateModule::open_dispute(Origin::signed(4), b"cid".to_vec(), true));
+		for juror in 1..=3u64 {
+			let commitment = BlakeTwo256::hash_of(&(juror, 0u64, false, b"salt".to_vec()));
+			assert_ok!(TemplateModule::commit_vote(Origin::signed(juror), 0, commitment));
+		}
+		// a commitment only reveals for the juror that made it
+		System::set_block_number(7);
+		assert_noop!(
+			TemplateModule::reveal_vote(Origin::signed(1), 0, true, b"salt".to_vec()),
+			"Vote doesn't match commitment"
+		);
+		for juror in 1..=3u64 {
+			assert_ok!(TemplateModule::reveal_vote(Origin::signed(juror), 0, false, b"salt".to_vec()));
+		}
+
+		System::set_block_number(12);
+		assert_ok!(TemplateModule::resolve_dispute(Origin::signed(6), 0));
+
+		// the opener's stake of 10 is split 4, 3, 3 on top of the jurors' own stakes
+		assert_eq!(TemplateModule::juror_stakes(1), 14);
+		assert_eq!(TemplateModule::juror_stakes(2), 13);
+		assert_eq!(TemplateModule::juror_stakes(3), 13);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 4), 90);
+		assert!(!TemplateModule::photos(b"cid".to_vec()).hidden);
+	});
+}
+
+#[test]
+fn dropped_imbalances_settle_the_total_issuance() {
+	new_test_ext().execute_with(|| {
+		init_social_token();
+
+		// credited tokens join the supply once the imbalance is dropped
+		let credited = <TemplateModule as Currency<u64>>::deposit_creating(&1, 50);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_000);
+		drop(credited);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_050);
+		assert_eq!(<TemplateModule as Currency<u64>>::free_balance(&1), 50);
+
+		// withdrawn ones leave it
+		let withdrawn =
+			<TemplateModule as Currency<u64>>::withdraw(&1, 20, WithdrawReasons::all(), ExistenceRequirement::AllowDeath);
+		drop(withdrawn.unwrap());
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_030);
+
+		// issuing without crediting anyone cancels out, burning without debiting anyone too
+		drop(<TemplateModule as Currency<u64>>::issue(100));
+		drop(<TemplateModule as Currency<u64>>::burn(100));
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_030);
+
+		// an issue resolved into an account keeps the supply and the balances in step
+		let issued = <TemplateModule as Currency<u64>>::issue(5);
+		<TemplateModule as Currency<u64>>::resolve_creating(&2, issued);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_035);
+		assert_eq!(<TemplateModule as Currency<u64>>::free_balance(&2), 5);
+	});
+}
+
+#[test]
+fn reserves_count_towards_the_total_balance_until_slashed() {
+	new_test_ext().execute_with(|| {
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 30));
+
+		assert_ok!(<TemplateModule as ReservableCurrency<u64>>::reserve(&1, 10));
+		assert_eq!(<TemplateModule as Currency<u64>>::free_balance(&1), 20);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_balance(&1), 30);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 1_000);
+
+		let (slashed, missing) = <TemplateModule as ReservableCurrency<u64>>::slash_reserved(&1, 15);
+		assert_eq!(missing, 5);
+		drop(slashed);
+		assert_eq!(<TemplateModule as ReservableCurrency<u64>>::reserved_balance(&1), 0);
+		assert_eq!(<TemplateModule as Currency<u64>>::total_issuance(), 990);
+	});
+}
+
+#[test]
+fn balance_at_keeps_the_balance_from_before_the_first_checkpoint() {
+	new_test_ext().execute_with(|| {
+		// a balance carried over by the migration has no checkpoints yet
+		put_storage_value(b"Erc20", b"BalanceOf", &Blake2_128Concat::hash(&1u64.encode()), 700u128);
+		TemplateModule::on_runtime_upgrade();
+		System::set_block_number(1);
+		init_social_token();
+
+		System::set_block_number(5);
+		assert_ok!(TemplateModule::transfer(Origin::signed(1), SOCIAL_TOKEN, 2, 200));
+		assert_eq!(TemplateModule::balance_at(1, 4), Some(700));
+		assert_eq!(TemplateModule::balance_at(1, 5), Some(500));
+		assert_eq!(TemplateModule::balance_at(2, 4), Some(0));
+	});
+}
+
+#[test]
+fn balance_at_forgets_blocks_older_than_the_kept_checkpoints() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_eq!(TemplateModule::balance_at(100, 0), Some(0));
+		assert_eq!(TemplateModule::total_supply_at(0), Some(0));
+
+		for n in 2..=MAX_CHECKPOINTS as u64 + 1 {
+			System::set_block_number(n);
+			assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 1));
+		}
+		assert_eq!(TemplateModule::balance_checkpoints(SOCIAL_TOKEN, 100).len(), MAX_CHECKPOINTS);
+		assert_eq!(TemplateModule::balance_at(100, 1), None);
+		assert_eq!(TemplateModule::balance_at(100, 2), Some(999));
+		assert_eq!(TemplateModule::balance_at(100, MAX_CHECKPOINTS as u64 + 1), Some(1_000 - MAX_CHECKPOINTS as u128));
+		assert_eq!(TemplateModule::total_supply_at(1), Some(1_000));
+	});
+}
+
+#[test]
+fn claim_airdrop_pays_the_leaves_of_the_merkle_tree_from_the_escrow() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		let alice = BlakeTwo256::hash_of(&(1u64, 300u128));
+		let bob = BlakeTwo256::hash_of(&(2u64, 200u128));
+		let root = if alice <= bob {
+			BlakeTwo256::hash_of(&(alice, bob))
+		} else {
+			BlakeTwo256::hash_of(&(bob, alice))
+		};
+
+		assert_ok!(TemplateModule::create_airdrop(Origin::signed(100), SOCIAL_TOKEN, root, 600));
+		let escrow = TemplateModule::_airdrop_account(0);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, escrow), 600);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 100), 400);
+
+		assert_ok!(TemplateModule::claim_airdrop(Origin::signed(1), 0, 300, vec![bob]));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 300);
+		assert_noop!(
+			TemplateModule::claim_airdrop(Origin::signed(1), 0, 300, vec![bob]),
+			"Airdrop already claimed"
+		);
+		assert_noop!(TemplateModule::claim_airdrop(Origin::signed(2), 0, 300, vec![alice]), "Invalid proof");
+
+		// bob's 200 and the 100 nobody can claim go back to the funder
+		assert_ok!(TemplateModule::close_airdrop(Origin::signed(100), 0));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, escrow), 0);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 100), 700);
+	});
+}
+
+#[test]
+fn all_or_nothing_batch_transfer_checks_every_transfer_first() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::freeze_account(Origin::signed(100), 3));
+
+		assert_noop!(
+			TemplateModule::batch_transfer(Origin::signed(100), SOCIAL_TOKEN, vec![(1, 10), (2, 10), (3, 10)], true),
+			"Account is frozen"
+		);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 0);
+
+		assert_ok!(TemplateModule::batch_transfer(Origin::signed(100), SOCIAL_TOKEN, vec![(1, 10), (2, 10), (3, 10)], false));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 10);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 3), 0);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 100), 980);
+	});
+}
+
+#[test]
+fn renewals_wait_for_transfers_to_be_unpaused() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+		assert_ok!(TemplateModule::set_subscription_tier(Origin::signed(2), 0, b"fan".to_vec(), 10));
+		assert_ok!(TemplateModule::approve(Origin::signed(1), SOCIAL_TOKEN, 2, 50));
+		assert_ok!(TemplateModule::subscribe(Origin::signed(1), 2, 0));
+
+		assert_ok!(TemplateModule::pause(Origin::signed(100)));
+		TemplateModule::on_initialize(31);
+		TemplateModule::on_initialize(32);
+		assert!(TemplateModule::subscriptions(1, 2).is_some());
+		assert_eq!(TemplateModule::renewals_at(31), vec![(1, 2)]);
+		assert!(TemplateModule::renewals_at(32).is_empty());
+
+		assert_ok!(TemplateModule::unpause(Origin::signed(100)));
+		TemplateModule::on_initialize(32);
+		assert_eq!(TemplateModule::subscriptions(1, 2).map(|s| s.paid_until), Some(62));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 2), 20);
+		assert_eq!(TemplateModule::renewal_cursor(), 33);
+	});
+}
+
+#[test]
+fn frozen_accounts_are_not_credited_withdrawn_or_slashed() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+		assert_ok!(TemplateModule::freeze_account(Origin::signed(100), 1));
+
+		assert_eq!(<TemplateModule as Currency<u64>>::deposit_creating(&1, 10).peek(), 0);
+		assert_noop!(<TemplateModule as ReservableCurrency<u64>>::reserve(&1, 10), "Account is frozen");
+		assert!(<TemplateModule as Currency<u64>>::withdraw(
+			&1,
+			10,
+			WithdrawReasons::all(),
+			ExistenceRequirement::KeepAlive
+		)
+		.is_err());
+		let (slashed, unslashed) = <TemplateModule as Currency<u64>>::slash(&1, 10);
+		assert_eq!((slashed.peek(), unslashed), (0, 10));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 100);
+	});
+}
+
+// jurors 1, 2 and 3 reveal `votes` on 4's request to hide 5's photo, 4 is frozen meanwhile
+fn frozen_opener_dispute(votes: &[(u64, bool)]) {
+	System::set_block_number(1);
+	init_social_token();
+	for who in 1..=4u64 {
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, who, 100));
+	}
+	for juror in 1..=3u64 {
+		assert_ok!(TemplateModule::stake_juror(Origin::signed(juror), 10));
+	}
+	let _ = Balances::deposit_creating(&5, 1_000);
+	assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"cid".to_vec(), None, None, Vec::new(), Vec::new()));
+	assert_ok!(TemplateModule::open_dispute(Origin::signed(4), b"cid".to_vec(), true));
+	for (juror, hide) in votes.iter() {
+		let commitment = BlakeTwo256::hash_of(&(*juror, 0u64, *hide, b"salt".to_vec()));
+		assert_ok!(TemplateModule::commit_vote(Origin::signed(*juror), 0, commitment));
+	}
+	assert_ok!(TemplateModule::freeze_account(Origin::signed(100), 4));
+	System::set_block_number(7);
+	for (juror, hide) in votes.iter() {
+		assert_ok!(TemplateModule::reveal_vote(Origin::signed(*juror), 0, *hide, b"salt".to_vec()));
+	}
+	System::set_block_number(12);
+}
+
+#[test]
+fn a_tie_settles_once_when_the_opener_is_frozen() {
+	new_test_ext().execute_with(|| {
+		frozen_opener_dispute(&[(1, true), (2, false)]);
+
+		assert_ok!(TemplateModule::resolve_dispute(Origin::signed(6), 0));
+		assert_noop!(TemplateModule::resolve_dispute(Origin::signed(6), 0), "Dispute doesn't exist");
+		for juror in 1..=3u64 {
+			assert_eq!(TemplateModule::juror_stakes(juror), 10);
+		}
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 4), 100);
+	});
+}
+
+#[test]
+fn a_frozen_opener_still_wins_a_dispute() {
+	new_test_ext().execute_with(|| {
+		frozen_opener_dispute(&[(1, true), (2, true), (3, true)]);
+
+		assert_ok!(TemplateModule::resolve_dispute(Origin::signed(6), 0));
+		assert!(TemplateModule::photos(b"cid".to_vec()).hidden);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 4), 100);
+		assert!(TemplateModule::disputes(0).is_none());
+	});
+}
+
+#[test]
+fn a_frozen_juror_keeps_the_stake() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+		assert_ok!(TemplateModule::stake_juror(Origin::signed(1), 10));
+		assert_ok!(TemplateModule::freeze_account(Origin::signed(100), 1));
+
+		assert_noop!(TemplateModule::unstake_juror(Origin::signed(1), 10), "Account is frozen");
+		assert_eq!(TemplateModule::juror_stakes(1), 10);
+		assert_eq!(TemplateModule::juror_pool(), vec![1]);
+	});
+}
+
+#[test]
+fn small_rewards_vest_over_the_whole_period() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+
+		// 6 tokens over `VestingPeriod` of 10 blocks, fewer tokens than blocks
+		TemplateModule::_add_vesting(&1, 6);
+		assert_eq!(TemplateModule::locks(1), vec![(VESTING_ID, 6)]);
+
+		System::set_block_number(6);
+		TemplateModule::_update_vesting_lock(&1);
+		assert_eq!(TemplateModule::locks(1), vec![(VESTING_ID, 3)]);
+		assert_noop!(TemplateModule::transfer(Origin::signed(1), SOCIAL_TOKEN, 2, 98), "Balance is locked");
+
+		System::set_block_number(11);
+		TemplateModule::_update_vesting_lock(&1);
+		assert!(TemplateModule::locks(1).is_empty());
+		assert!(TemplateModule::vesting(1).is_empty());
+	});
+}
+
+#[test]
+fn comments_reserve_a_social_token_deposit_until_deleted() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 10));
+		let _ = Balances::deposit_creating(&5, 1_000);
+		assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"cid".to_vec(), None, None, Vec::new(), Vec::new()));
+
+		assert_ok!(TemplateModule::comment_photo(Origin::signed(1), b"cid".to_vec(), b"nice".to_vec(), Vec::new()));
+		assert_ok!(TemplateModule::comment_photo(Origin::signed(1), b"cid".to_vec(), b"again".to_vec(), Vec::new()));
+		assert_eq!(<TemplateModule as ReservableCurrency<u64>>::reserved_balance(&1), 2);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 8);
+		// an account without social tokens can't comment
+		assert!(TemplateModule::comment_photo(Origin::signed(2), b"cid".to_vec(), b"hi".to_vec(), Vec::new()).is_err());
+
+		assert_ok!(TemplateModule::delete_comment(Origin::signed(1), b"cid".to_vec(), 0));
+		assert_eq!(<TemplateModule as ReservableCurrency<u64>>::reserved_balance(&1), 1);
+		assert_eq!(TemplateModule::comment_count(b"cid".to_vec()), 1);
+		assert!(TemplateModule::comments(b"cid".to_vec(), 1).is_some());
+
+		// deleting the photo returns the deposits of the comments left on it
+		assert_ok!(TemplateModule::delete_photo(Origin::signed(5), b"cid".to_vec()));
+		assert_eq!(<TemplateModule as ReservableCurrency<u64>>::reserved_balance(&1), 0);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 10);
+		assert!(TemplateModule::comments(b"cid".to_vec(), 1).is_none());
+	});
+}
+
+#[test]
+fn a_photo_holds_a_bounded_number_of_comments() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		init_social_token();
+		let _ = Balances::deposit_creating(&5, 1_000);
+		assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"cid".to_vec(), None, None, Vec::new(), Vec::new()));
+
+		for _ in 0..MAX_COMMENTS_PER_PHOTO {
+			assert_ok!(TemplateModule::comment_photo(Origin::signed(100), b"cid".to_vec(), b"hi".to_vec(), Vec::new()));
+		}
+		assert_noop!(
+			TemplateModule::comment_photo(Origin::signed(100), b"cid".to_vec(), b"hi".to_vec(), Vec::new()),
+			"Too many comments"
+		);
+		// a deleted comment frees its place, its index isn't reused
+		assert_ok!(TemplateModule::delete_comment(Origin::signed(100), b"cid".to_vec(), 0));
+		assert_ok!(TemplateModule::comment_photo(Origin::signed(100), b"cid".to_vec(), b"hi".to_vec(), Vec::new()));
+		assert!(TemplateModule::comments(b"cid".to_vec(), MAX_COMMENTS_PER_PHOTO).is_some());
+	});
+}
+
+#[test]
+fn profile_bios_and_links_reserve_a_deposit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		// a name and an avatar need no balance
+		assert_ok!(TemplateModule::update_user(Origin::signed(1), Some(b"alice".to_vec()), Some(b"img".to_vec()), None, None));
+		assert!(TemplateModule::update_user(Origin::signed(1), None, None, Some(b"bio".to_vec()), None).is_err());
+
+		let _ = Balances::deposit_creating(&1, 100);
+		assert_ok!(TemplateModule::update_user(Origin::signed(1), None, None, Some(b"bio".to_vec()), Some(vec![b"link".to_vec()])));
+		assert_eq!(Balances::reserved_balance(&1), 7);
+		assert_ok!(TemplateModule::update_user(Origin::signed(1), None, None, Some(Vec::new()), None));
+		assert_eq!(Balances::reserved_balance(&1), 4);
+	});
+}
+
+#[test]
+fn a_handle_only_moves_once_the_receiver_accepts_it() {
+	new_test_ext().execute_with(|| {
+		let _ = Balances::deposit_creating(&1, 100);
+		let _ = Balances::deposit_creating(&2, 100);
+		assert_ok!(TemplateModule::claim_handle(Origin::signed(1), b"alice".to_vec()));
+
+		// offering reserves nothing from the receiver
+		assert_ok!(TemplateModule::transfer_handle(Origin::signed(1), 2));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(TemplateModule::account_of_handle(b"alice".to_vec()), Some(1));
+		assert_noop!(
+			TemplateModule::accept_handle(Origin::signed(3), 1),
+			"Handle was not offered to this account"
+		);
+
+		assert_ok!(TemplateModule::accept_handle(Origin::signed(2), 1));
+		assert_eq!(TemplateModule::account_of_handle(b"alice".to_vec()), Some(2));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert!(TemplateModule::handle_offer(1).is_none());
+	});
+}
+
+#[test]
+fn reports_about_deleted_content_can_be_closed() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let _ = Balances::deposit_creating(&5, 1_000);
+		assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"cid".to_vec(), None, None, Vec::new(), Vec::new()));
+		assert_ok!(TemplateModule::upload_photo(Origin::signed(5), b"other".to_vec(), None, None, Vec::new(), Vec::new()));
+		assert_ok!(TemplateModule::add_moderator(Origin::root(), 9));
+		assert_ok!(TemplateModule::report_photo(Origin::signed(1), b"cid".to_vec(), ReportReason::Spam));
+		assert_ok!(TemplateModule::report_photo(Origin::signed(1), b"other".to_vec(), ReportReason::Spam));
+		assert_ok!(TemplateModule::delete_photo(Origin::signed(5), b"cid".to_vec()));
+
+		assert_ok!(TemplateModule::moderate(Origin::signed(9), 0, true));
+		assert_eq!(TemplateModule::reports(0).map(|r| r.status), Some(ReportStatus::Closed));
+
+		// only the report still waiting for a moderator is listed
+		let pending: Vec<u64> = TemplateModule::pending_reports(0, 10).into_iter().map(|(id, _)| id).collect();
+		assert_eq!(pending, vec![1]);
+		assert_ok!(TemplateModule::moderate(Origin::signed(9), 1, false));
+		assert!(TemplateModule::pending_reports(0, 10).is_empty());
+	});
+}
+
+fn fee_info() -> DispatchInfo {
+	DispatchInfo { weight: 5, class: DispatchClass::Normal, pays_fee: Pays::Yes }
+}
+
+#[test]
+fn fees_are_paid_natively_unless_asked_otherwise() {
+	new_test_ext().execute_with(|| {
+		let _ = Balances::deposit_creating(&1, 1_000);
+		let call = Call::TemplateModule(crate::Call::like_photo(b"cid".to_vec()));
+		let fee = pallet_transaction_payment::Module::<Test>::compute_fee(10, &fee_info(), 0);
+		assert!(fee > 0);
+
+		assert_ok!(ChargeFeeInToken::<Test>::native(0).pre_dispatch(&1, &call, &fee_info(), 10));
+		assert_eq!(Balances::free_balance(&1), 1_000 - fee);
+	});
+}
+
+#[test]
+fn fees_paid_in_tokens_go_to_the_treasury_at_the_fee_rate() {
+	new_test_ext().execute_with(|| {
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+		let call = Call::TemplateModule(crate::Call::like_photo(b"cid".to_vec()));
+		// no rate set, tokens can't pay fees
+		assert!(ChargeFeeInToken::<Test>::token(0).validate(&1, &call, &fee_info(), 10).is_err());
+
+		assert_ok!(TemplateModule::set_fee_rate(Origin::root(), Some(FixedU128::saturating_from_integer(2))));
+		let fee = pallet_transaction_payment::Module::<Test>::compute_fee(10, &fee_info(), 0) as u128 * 2;
+		assert_ok!(ChargeFeeInToken::<Test>::token(0).pre_dispatch(&1, &call, &fee_info(), 10));
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 1), 100 - fee);
+		assert_eq!(TemplateModule::balance_of(SOCIAL_TOKEN, 100), 900 + fee);
+		assert_eq!(Balances::free_balance(&1), 0);
+	});
+}
+
+#[test]
+fn token_fees_are_rejected_at_validation_when_frozen() {
+	new_test_ext().execute_with(|| {
+		init_social_token();
+		assert_ok!(TemplateModule::transfer(Origin::signed(100), SOCIAL_TOKEN, 1, 100));
+		assert_ok!(TemplateModule::set_fee_rate(Origin::root(), Some(FixedU128::saturating_from_integer(1))));
+		let call = Call::TemplateModule(crate::Call::like_photo(b"cid".to_vec()));
+		let rejected: TransactionValidity = Err(InvalidTransaction::Payment.into());
+
+		assert_ok!(TemplateModule::freeze_account(Origin::root(), 1));
+		assert_eq!(ChargeFeeInToken::<Test>::token(0).validate(&1, &call, &fee_info(), 10), rejected);
+		assert_ok!(TemplateModule::thaw_account(Origin::root(), 1));
+
+		// a frozen treasury can't receive the fee of anyone
+		assert_ok!(TemplateModule::freeze_account(Origin::root(), 100));
+		assert_eq!(ChargeFeeInToken::<Test>::token(0).validate(&1, &call, &fee_info(), 10), rejected);
+	});
+}
+
+#[test]
+fn free_actions_are_rejected_over_the_quota() {
+	new_test_ext().execute_with(|| {
+		let free = Call::TemplateModule(crate::Call::update_user(None, None, None, None));
+		for _ in 0..FreeActionsPerEra::get() {
+			assert_ok!(LimitFreeActions::<Test>::new().pre_dispatch(&1, &free, &fee_info(), 10));
+		}
+		let exhausted: TransactionValidity = Err(InvalidTransaction::ExhaustsResources.into());
+		assert_eq!(LimitFreeActions::<Test>::new().validate(&1, &free, &fee_info(), 10), exhausted);
+
+		// paid calls don't count against the quota, other accounts have their own
+		let paid = Call::TemplateModule(crate::Call::claim_handle(b"alice".to_vec()));
+		assert_ok!(LimitFreeActions::<Test>::new().validate(&1, &paid, &fee_info(), 10));
+		assert_ok!(LimitFreeActions::<Test>::new().validate(&2, &free, &fee_info(), 10));
+
+		// the quota is back in the next era
+		System::set_block_number(RewardEraLength::get());
+		assert_ok!(LimitFreeActions::<Test>::new().validate(&1, &free, &fee_info(), 10));
+	});
+}
@@ -106,10 +106,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("node-template"),
     impl_name: create_runtime_str!("node-template"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 2,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -126,6 +126,14 @@ pub const MILLICENTS: Balance = 1_000_000_000;
 pub const CENTS: Balance = 1_000 * MILLICENTS;
 pub const DOLLARS: Balance = 100 * CENTS;
 
+/// Decimals and symbol wallets use to display balances, one unit is one `DOLLARS`.
+pub const TOKEN_DECIMALS: u32 = 14;
+pub const TOKEN_SYMBOL: &str = "SHINE";
+/// Decimals and ticker of the social token, `test_pallet` enforces both on chain.
+/// Its balances have always been whole tokens.
+pub const SOCIAL_TOKEN_DECIMALS: u8 = 0;
+pub const SOCIAL_TOKEN_TICKER: &str = "SHINEDME";
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -295,10 +303,56 @@ impl sudo::Trait for Runtime {
 // 	type Event = template::Event;
 // }
 
+parameter_types! {
+    pub const HandleDeposit: Balance = 1 * DOLLARS;
+    pub const DepositPerByte: Balance = 10 * MILLICENTS;
+    pub const CommentDeposit: u128 = 1;
+    pub const RewardEraLength: BlockNumber = 1 * DAYS;
+    pub const MaxRewardPerEra: u128 = 5_000;
+    pub const MinAccountAge: BlockNumber = 1 * HOURS;
+    pub const RewardEpochLength: BlockNumber = 1 * HOURS;
+    pub const RewardPool: u128 = 1_000;
+    /// Halves a trending score roughly every 12 hours.
+    pub const TrendingDecay: Perbill = Perbill::from_parts(999_904_000);
+    pub const TrendingSize: u32 = 100;
+    pub const JurySize: u32 = 5;
+    pub const JuryStake: u128 = 100;
+    pub const CommitPeriod: BlockNumber = 1 * DAYS;
+    pub const RevealPeriod: BlockNumber = 1 * DAYS;
+    pub const SubscriptionPeriod: BlockNumber = 30 * DAYS;
+    pub const FreeActionsPerEra: u32 = 20;
+    pub const VestedRewardShare: Perbill = Perbill::from_percent(50);
+    pub const VestingPeriod: BlockNumber = 7 * DAYS;
+    pub const SocialTokenDecimals: u8 = SOCIAL_TOKEN_DECIMALS;
+    pub SocialTokenTicker: Vec<u8> = SOCIAL_TOKEN_TICKER.as_bytes().to_vec();
+}
+
 // add the following code block
 impl test_pallet::Trait for Runtime {
     type Event = Event;
     type TokenBalance = u128;
+    type Currency = Balances;
+    type HandleDeposit = HandleDeposit;
+    type DepositPerByte = DepositPerByte;
+    type CommentDeposit = CommentDeposit;
+    type RewardEraLength = RewardEraLength;
+    type MaxRewardPerEra = MaxRewardPerEra;
+    type MinAccountAge = MinAccountAge;
+    type RewardEpochLength = RewardEpochLength;
+    type RewardPool = RewardPool;
+    type TrendingDecay = TrendingDecay;
+    type TrendingSize = TrendingSize;
+    type Randomness = RandomnessCollectiveFlip;
+    type JurySize = JurySize;
+    type JuryStake = JuryStake;
+    type CommitPeriod = CommitPeriod;
+    type RevealPeriod = RevealPeriod;
+    type SubscriptionPeriod = SubscriptionPeriod;
+    type FreeActionsPerEra = FreeActionsPerEra;
+    type VestedRewardShare = VestedRewardShare;
+    type VestingPeriod = VestingPeriod;
+    type SocialTokenDecimals = SocialTokenDecimals;
+    type SocialTokenTicker = SocialTokenTicker;
 }
 
 construct_runtime!(
@@ -318,7 +372,7 @@ construct_runtime!(
         // Used for the module template in `./template.rs`
         // TemplateModule: template::{Module, Call, Storage, Event<T>},
         Contracts: contracts::{Module, Call, Config, Storage, Event<T>},
-        Erc20: test_pallet::{Module, Call, Storage, Event<T>},
+        Erc20: test_pallet::{Module, Call, Storage, Event<T>, Config},
     }
 );
 
@@ -340,7 +394,10 @@ pub type SignedExtra = (
     system::CheckEra<Runtime>,
     system::CheckNonce<Runtime>,
     system::CheckWeight<Runtime>,
-    transaction_payment::ChargeTransactionPayment<Runtime>,
+    // charges fees in the native currency or the social token, see `test_pallet::ChargeFeeInToken`
+    test_pallet::ChargeFeeInToken<Runtime>,
+    // caps the feeless social actions per era, see `test_pallet::LimitFreeActions`
+    test_pallet::LimitFreeActions<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -493,4 +550,44 @@ impl_runtime_apis! {
             Contracts::rent_projection(address)
         }
     }
+
+    impl test_pallet_runtime_api::SocialApi<
+        Block,
+        AccountId,
+        test_pallet::Report<AccountId, BlockNumber>,
+        u128,
+        BlockNumber,
+    > for Runtime
+    {
+        fn photos_by_tag(tag: Vec<u8>, start: u32, limit: u32) -> Vec<Vec<u8>> {
+            Erc20::photos_by_tag_page(tag, start, limit)
+        }
+
+        fn followers(account: AccountId, start: u32, limit: u32) -> Vec<AccountId> {
+            Erc20::followers_page(account, start, limit)
+        }
+
+        fn trending(limit: u32) -> Vec<(Vec<u8>, u64)> {
+            Erc20::trending_page(limit)
+        }
+
+        fn is_subscribed(subscriber: AccountId, creator: AccountId) -> bool {
+            Erc20::is_subscribed(subscriber, creator)
+        }
+
+        fn pending_reports(
+            start: u32,
+            limit: u32,
+        ) -> Vec<(u64, test_pallet::Report<AccountId, BlockNumber>)> {
+            Erc20::pending_reports(start, limit)
+        }
+
+        fn balance_at(account: AccountId, block: BlockNumber) -> Option<u128> {
+            Erc20::balance_at(account, block)
+        }
+
+        fn total_supply_at(block: BlockNumber) -> Option<u128> {
+            Erc20::total_supply_at(block)
+        }
+    }
 }